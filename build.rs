@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+/// Dependency whose resolved source is reported in diagnostic bundles
+const PROTOCOL_CRATE: &str = "reciprocity_communication";
+
+fn main() {
+    //The lockfile is not tracked, but cargo always writes it before building
+    let lock =
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock.display());
+    let source = std::fs::read_to_string(&lock)
+        .ok()
+        .and_then(|lock| locked_package(&lock, PROTOCOL_CRATE))
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=PROTOCOL_SOURCE={}", source);
+}
+
+/// Version and source of the locked package, for git dependencies the source ends with the commit
+fn locked_package(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let package = lock
+        .split("[[package]]")
+        .find(|p| p.lines().any(|l| l.trim() == name_line))?;
+    let field = |key: &str| {
+        package
+            .lines()
+            .find_map(|l| l.trim().strip_prefix(key)?.trim_start().strip_prefix('='))
+            .map(|v| v.trim().trim_matches('"'))
+    };
+    Some(format!(
+        "{} {}",
+        field("version")?,
+        field("source").unwrap_or("local")
+    ))
+}
@@ -0,0 +1,115 @@
+use crate::error::Error;
+use crate::theme::Theme;
+use crate::Message;
+use iced::{Align, Button, Column, Container, Element, Length, Row, Text};
+
+/// Banners shown at once, older ones are dropped
+const MAX_BANNERS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub enum BannerMessage {
+    Dismiss(usize),
+    Retry(usize),
+}
+
+#[derive(Debug)]
+struct Banner {
+    id: usize,
+    error: Error,
+    /// How often the same error occurred while the banner was shown
+    count: usize,
+
+    retry_button_state: iced::button::State,
+    dismiss_button_state: iced::button::State,
+}
+
+/// Errors shown above the tabs until the user dismisses them
+#[derive(Debug)]
+pub struct Banners {
+    banners: Vec<Banner>,
+    next_id: usize,
+}
+
+impl Banners {
+    pub fn new() -> Self {
+        Banners {
+            banners: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn push(&mut self, error: Error) {
+        let text = error.to_string();
+        if let Some(banner) = self.banners.iter_mut().find(|b| b.error.to_string() == text) {
+            banner.count += 1;
+            return;
+        }
+        if self.banners.len() >= MAX_BANNERS {
+            self.banners.remove(0);
+        }
+        self.banners.push(Banner {
+            id: self.next_id,
+            error,
+            count: 1,
+            retry_button_state: Default::default(),
+            dismiss_button_state: Default::default(),
+        });
+        self.next_id += 1;
+    }
+
+    /// Handles the message, returning the message retrying the failed action
+    pub fn update(&mut self, message: BannerMessage) -> Option<Message> {
+        match message {
+            BannerMessage::Dismiss(id) => {
+                self.banners.retain(|b| b.id != id);
+                None
+            }
+            BannerMessage::Retry(id) => {
+                let index = self.banners.iter().position(|b| b.id == id)?;
+                self.banners.remove(index).error.retry()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.banners.is_empty()
+    }
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, Message> {
+        let mut column = Column::new().spacing(5).width(Length::Fill);
+        for banner in self.banners.iter_mut() {
+            let text = match banner.count {
+                1 => banner.error.to_string(),
+                n => format!("{} ({}x)", banner.error, n),
+            };
+            let mut row = Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    Text::new(text)
+                        .size(16)
+                        .color(theme.error_color())
+                        .width(Length::Fill),
+                );
+            if banner.error.retry().is_some() {
+                row = row.push(
+                    Button::new(&mut banner.retry_button_state, Text::new("Retry").size(16))
+                        .on_press(Message::Banner(BannerMessage::Retry(banner.id)))
+                        .style(theme.tab_button_theme()),
+                );
+            }
+            row = row.push(
+                Button::new(&mut banner.dismiss_button_state, Text::new("Dismiss").size(16))
+                    .on_press(Message::Banner(BannerMessage::Dismiss(banner.id)))
+                    .style(theme.tab_button_theme()),
+            );
+            column = column.push(row);
+        }
+
+        Container::new(column)
+            .width(Length::Fill)
+            .padding(5)
+            .style(theme.tab_view_container_theme())
+            .into()
+    }
+}
@@ -0,0 +1,153 @@
+use crate::config::Config;
+use crate::library::{Library, SavedPlaylist};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Version written into exported bundles, bumped on incompatible changes
+pub const BUNDLE_VERSION: u32 = 1;
+pub const BUNDLE_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+/// Settings and saved playlists to hand to somebody else, without any secrets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub config: Config,
+    /// Missing in bundles exported before playlists were included, which keep the library
+    #[serde(default)]
+    pub playlists: Option<Vec<SavedPlaylist>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Adds the profiles and playlists of the bundle, keeping everything else
+    Merge,
+    /// Takes over the profiles and playlists of the bundle, keeping the settings of this machine
+    Replace,
+}
+
+#[derive(Debug, Clone)]
+pub enum BundleMessage {
+    Export(),
+    Exported(Result<Option<PathBuf>, String>),
+    Import(),
+    Loaded(Result<Option<SettingsBundle>, String>),
+    Apply(ImportMode),
+    Cancel(),
+}
+
+impl SettingsBundle {
+    pub fn new(cfg: &Config, library: &Library) -> Self {
+        let mut config = cfg.clone();
+        for profile in config.profiles.values_mut() {
+            profile.refresh_token = None;
+        }
+        SettingsBundle {
+            version: BUNDLE_VERSION,
+            config,
+            playlists: Some(library.playlists.clone()),
+        }
+    }
+
+    pub async fn save(self, path: PathBuf) -> Result<PathBuf, String> {
+        let bytes = serde_yaml::to_vec(&self).map_err(|e| e.to_string())?;
+        File::create(&path)
+            .and_then(|mut f| f.write_all(bytes.as_slice()))
+            .map_err(|e| format!("Could not write {:?}: {}", path, e))?;
+        Ok(path)
+    }
+
+    pub async fn load(path: PathBuf) -> Result<Self, String> {
+        let file = File::open(&path).map_err(|e| format!("Could not open {:?}: {}", path, e))?;
+        let mut bundle: SettingsBundle =
+            serde_yaml::from_reader(file).map_err(|e| format!("Invalid bundle: {}", e))?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!(
+                "Bundle version {} is newer than the supported version {}",
+                bundle.version, BUNDLE_VERSION
+            ));
+        }
+        bundle.config.normalize();
+        Ok(bundle)
+    }
+
+    /// Describes what importing the bundle would change, one line per entry
+    pub fn preview(&self, cfg: &Config, library: &Library) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, profile) in self.config.profiles.iter() {
+            let state = match cfg.profiles.get(name) {
+                None => "new",
+                Some(p)
+                    if p.bot_link == profile.bot_link
+                        && p.com.client_id == profile.com.client_id =>
+                {
+                    "unchanged"
+                }
+                Some(_) => "changed",
+            };
+            lines.push(format!(
+                "Profile {} ({}): {}",
+                name, state, profile.bot_link
+            ));
+        }
+        for playlist in self.playlists.iter().flatten() {
+            let state = match library.playlists.iter().find(|p| p.name == playlist.name) {
+                None => "new",
+                Some(p) if p.tracks == playlist.tracks => "unchanged",
+                Some(_) => "changed",
+            };
+            lines.push(format!(
+                "Playlist {} ({}): {} tracks",
+                playlist.name,
+                state,
+                playlist.tracks.len()
+            ));
+        }
+        lines
+    }
+
+    /// Applies the profiles and playlists of the bundle, keeping the refresh tokens of profiles
+    /// which stay. Shortcuts, logging, appearance and other preferences are not imported.
+    pub fn apply(self, cfg: &mut Config, library: &mut Library, mode: ImportMode) {
+        let mut imported = self.config;
+        for (name, profile) in imported.profiles.iter_mut() {
+            profile.refresh_token = cfg
+                .profiles
+                .get(name)
+                .filter(|p| p.bot_link == profile.bot_link)
+                .map(|p| p.refresh_token.clone())
+                .flatten();
+        }
+
+        if let Some(playlists) = self.playlists {
+            match mode {
+                ImportMode::Merge => {
+                    for playlist in playlists {
+                        let existing = library
+                            .playlists
+                            .iter_mut()
+                            .find(|p| p.name == playlist.name);
+                        match existing {
+                            Some(existing) => *existing = playlist,
+                            None => library.playlists.push(playlist),
+                        }
+                    }
+                }
+                ImportMode::Replace => library.playlists = playlists,
+            }
+        }
+
+        match mode {
+            ImportMode::Merge => cfg.profiles.extend(imported.profiles),
+            ImportMode::Replace => {
+                //The active profile only changes if the bundle does not contain it
+                if !imported.profiles.contains_key(&cfg.active_profile) {
+                    cfg.active_profile = imported.active_profile;
+                }
+                cfg.profiles = imported.profiles;
+            }
+        }
+        cfg.normalize();
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::ConfigOverrides;
+use crate::theme::Theme;
+use log::LevelFilter;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Environment variable which disables authentication like `--no-auth`
+pub const NO_AUTH_ENV: &str = "RECIPROCITY_NO_AUTH";
+/// Environment variable which persists overrides like `--save-overrides`
+pub const SAVE_OVERRIDES_ENV: &str = "RECIPROCITY_SAVE_OVERRIDES";
+/// Environment variable which enables JSON log files like `--log-json`
+pub const LOG_JSON_ENV: &str = "RECIPROCITY_LOG_JSON";
+
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(name = "Reciprocity Companion", about = "Desktop companion for the Reciprocity bot")]
+pub struct Opt {
+    /// Path of the config file
+    #[structopt(long, env = "RECIPROCITY_CONFIG", parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Connection profile to use, must exist in the config
+    #[structopt(long, env = "RECIPROCITY_PROFILE")]
+    pub profile: Option<String>,
+    /// Websocket link of the bot, overrides the config
+    #[structopt(long, env = "RECIPROCITY_BOT_LINK")]
+    pub bot_link: Option<String>,
+    /// Theme to use (light, dark), overrides the config
+    #[structopt(long, env = "RECIPROCITY_THEME")]
+    pub theme: Option<Theme>,
+    /// Log level (off, error, warn, info, debug, trace)
+    #[structopt(long, env = "RECIPROCITY_LOG_LEVEL")]
+    pub log_level: Option<LevelFilter>,
+    /// Log level of a single module as `module=level`, can be given multiple times
+    #[structopt(
+        long = "log-module",
+        env = "RECIPROCITY_LOG_MODULES",
+        use_delimiter = true,
+        parse(try_from_str = parse_log_module)
+    )]
+    pub log_modules: Vec<(String, LevelFilter)>,
+    /// Write the log file as JSON lines
+    #[structopt(long)]
+    pub log_json: bool,
+    /// Directory for logs and local data
+    #[structopt(long, env = "RECIPROCITY_DATA_DIR", parse(from_os_str))]
+    pub data_dir: Option<PathBuf>,
+    /// Start without authenticating at the bot
+    #[structopt(long)]
+    pub no_auth: bool,
+    /// Write the overrides from the command line and environment back into the config
+    #[structopt(long)]
+    pub save_overrides: bool,
+}
+
+impl Opt {
+    /// Parses the command line, also respecting the `RECIPROCITY_*` environment variables
+    pub fn load() -> Self {
+        let mut opt = Opt::from_args();
+        opt.no_auth |= env_flag(NO_AUTH_ENV);
+        opt.save_overrides |= env_flag(SAVE_OVERRIDES_ENV);
+        opt.log_json |= env_flag(LOG_JSON_ENV);
+        opt
+    }
+
+    pub fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides::new(
+            self.profile.clone(),
+            self.bot_link.clone(),
+            self.theme,
+            self.log_level,
+            self.log_modules.clone(),
+            self.log_json,
+            self.save_overrides,
+        )
+    }
+}
+
+fn parse_log_module(s: &str) -> Result<(String, LevelFilter), String> {
+    let (module, level) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected module=level, got {}", s))?;
+    let level = level
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid log level {}", level))?;
+    Ok((module.trim().to_string(), level))
+}
+
+fn env_flag(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(v) => !matches!(v.to_lowercase().as_str(), "" | "0" | "false" | "no"),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+/// Deviations from the reported position up to this are corrected smoothly, larger ones at once
+const MAX_CORRECTION: Duration = Duration::from_secs(2);
+/// Time over which a deviation is corrected
+const CORRECTION_TIME: Duration = Duration::from_secs(1);
+
+/// Position of the playing track between the state updates of the bot
+#[derive(Debug, Clone)]
+pub struct PlaybackClock {
+    /// Position at `anchor`
+    pos: Duration,
+    anchor: Instant,
+    playing: bool,
+    len: Duration,
+    /// Shown minus actual position in seconds when the last report arrived, fading out
+    correction: f64,
+    /// Position and pause state of the last report, to skip repeated ones
+    last_report: Option<(Duration, bool)>,
+}
+
+impl PlaybackClock {
+    pub fn new() -> Self {
+        PlaybackClock {
+            pos: Duration::ZERO,
+            anchor: Instant::now(),
+            playing: false,
+            len: Duration::ZERO,
+            correction: 0.0,
+            last_report: None,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Takes over a position reported by the bot, which was sent `latency` ago
+    pub fn sync(&mut self, pos: Duration, len: Duration, paused: bool, latency: Duration) {
+        self.len = len;
+        let now = Instant::now();
+        let shown = self.position_at(now);
+        //Parts of the state unrelated to the track repeat the last position. The same position
+        //behind a clock at the end means the track started over, e.g. when looping it.
+        let restarted = !len.is_zero() && shown >= len && pos < shown;
+        if self.last_report == Some((pos, paused)) && !restarted {
+            return;
+        }
+        self.last_report = Some((pos, paused));
+
+        let actual = match paused {
+            true => pos,
+            false => pos + latency,
+        };
+        let deviation = shown.as_secs_f64() - actual.as_secs_f64();
+        //Only drift while playing is smoothed, pauses and seeks show at once
+        let drift = self.playing && !paused && deviation.abs() <= MAX_CORRECTION.as_secs_f64();
+        self.correction = match drift {
+            true => deviation,
+            false => 0.0,
+        };
+        self.pos = actual;
+        self.anchor = now;
+        self.playing = !paused;
+    }
+
+    /// Jumps to the position right away, e.g. while waiting for the bot to confirm a seek
+    pub fn seek(&mut self, pos: Duration) {
+        self.pos = pos;
+        self.anchor = Instant::now();
+        self.correction = 0.0;
+    }
+
+    pub fn position(&self) -> Duration {
+        self.position_at(Instant::now())
+    }
+
+    fn position_at(&self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.anchor);
+        let actual = match self.playing {
+            true => self.pos + elapsed,
+            false => self.pos,
+        };
+        let fade = 1.0 - elapsed.as_secs_f64() / CORRECTION_TIME.as_secs_f64();
+        let shown = actual.as_secs_f64() + self.correction * fade.max(0.0);
+        let shown = Duration::from_secs_f64(shown.max(0.0));
+        //Tracks without a length, like streams, are not clamped
+        match self.len.is_zero() {
+            true => shown,
+            false => shown.min(self.len),
+        }
+    }
+}
@@ -1,21 +1,119 @@
+use crate::shortcuts::Action;
 use crate::theme::Theme;
+use crate::MAX_DOUBLE_CLICK_INTERVAL;
+use log::LevelFilter;
 use reciprocity_communication::client::Config as ComConfig;
 use reciprocity_communication::messages::oauth2::RefreshToken;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::OpenOptions;
 use std::io::{Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::time::Duration;
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_FONT_SIZE: u16 = 22;
+pub const DEFAULT_THUMBNAIL_CACHE_SIZE: usize = 100;
+pub const DEFAULT_SEARCH_BACKEND: &str = "http://youtube-scrape.herokuapp.com/api/search?page=1";
+/// Seconds the rewind and forward buttons skip
+pub const DEFAULT_SEEK_STEP: u64 = 10;
+pub const DEFAULT_LOG_FILE_SIZE_MB: u64 = 10;
+pub const DEFAULT_LOG_FILE_AGE_DAYS: u64 = 7;
+pub const DEFAULT_LOG_FILE_COUNT: u32 = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default = "default_profile_name")]
+    pub active_profile: String,
+    #[serde(default)]
+    pub theme: Theme,
+    /// In milliseconds
+    #[serde(default = "default_double_click_interval")]
+    pub double_click_interval: u64,
+    #[serde(default = "default_font_size")]
+    pub font_size: u16,
+    /// In seconds
+    #[serde(default = "default_seek_step")]
+    pub rewind_step: u64,
+    /// In seconds
+    #[serde(default = "default_seek_step")]
+    pub forward_step: u64,
+    #[serde(default = "default_log_level")]
+    pub log_level: LevelFilter,
+    #[serde(default)]
+    pub logging: LogSettings,
+    #[serde(default = "default_thumbnail_cache_size")]
+    pub thumbnail_cache_size: usize,
+    #[serde(default = "default_search_backend")]
+    pub search_backend: String,
+    /// Key bindings replacing the defaults, an empty binding unbinds the action
+    #[serde(default)]
+    pub shortcuts: BTreeMap<Action, String>,
+
+    //Connection settings of configs from before profiles existed, moved into a profile on load
+    #[serde(default, skip_serializing)]
+    com: Option<ComConfig>,
+    #[serde(default, skip_serializing)]
+    refresh_token: Option<RefreshToken>,
+    #[serde(default, skip_serializing)]
+    bot_link: Option<String>,
+}
+
+/// Everything needed to connect to one bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
     #[serde(default = "default_com")]
     pub com: ComConfig,
     #[serde(default)]
     pub refresh_token: Option<RefreshToken>,
     #[serde(default = "default_bot_link")]
     pub bot_link: String,
+}
+
+/// Where and how the log files are written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogSettings {
+    /// Levels for single modules, e.g. `reciprocity_companion::connection: debug`
     #[serde(default)]
-    pub theme: Theme,
+    pub modules: BTreeMap<String, LevelFilter>,
+    /// Size after which the log file is rotated, 0 disables it
+    #[serde(default = "default_log_file_size_mb")]
+    pub max_file_size_mb: u64,
+    /// Age after which the log file is rotated, 0 disables it
+    #[serde(default = "default_log_file_age_days")]
+    pub max_file_age_days: u64,
+    /// Number of rotated log files kept
+    #[serde(default = "default_log_file_count")]
+    pub max_files: u32,
+    /// Writes one JSON object per line instead of plain text
+    #[serde(default)]
+    pub json: bool,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        LogSettings {
+            modules: BTreeMap::new(),
+            max_file_size_mb: default_log_file_size_mb(),
+            max_file_age_days: default_log_file_age_days(),
+            max_files: default_log_file_count(),
+            json: false,
+        }
+    }
+}
+
+fn default_log_file_size_mb() -> u64 {
+    DEFAULT_LOG_FILE_SIZE_MB
+}
+
+fn default_log_file_age_days() -> u64 {
+    DEFAULT_LOG_FILE_AGE_DAYS
+}
+
+fn default_log_file_count() -> u32 {
+    DEFAULT_LOG_FILE_COUNT
 }
 
 fn default_com() -> ComConfig {
@@ -30,29 +128,367 @@ fn default_bot_link() -> String {
     "ws://autumnal.de:1337".to_string()
 }
 
-impl Default for Config {
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_double_click_interval() -> u64 {
+    MAX_DOUBLE_CLICK_INTERVAL.as_millis() as u64
+}
+
+fn default_font_size() -> u16 {
+    DEFAULT_FONT_SIZE
+}
+
+fn default_seek_step() -> u64 {
+    DEFAULT_SEEK_STEP
+}
+
+fn default_log_level() -> LevelFilter {
+    LevelFilter::Info
+}
+
+fn default_thumbnail_cache_size() -> usize {
+    DEFAULT_THUMBNAIL_CACHE_SIZE
+}
+
+fn default_search_backend() -> String {
+    DEFAULT_SEARCH_BACKEND.to_string()
+}
+
+impl Default for Profile {
     fn default() -> Self {
-        Config {
+        Profile {
             com: default_com(),
             refresh_token: None,
             bot_link: default_bot_link(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(default_profile_name(), Profile::default());
+        Config {
+            profiles,
+            active_profile: default_profile_name(),
             theme: Default::default(),
+            double_click_interval: default_double_click_interval(),
+            font_size: default_font_size(),
+            rewind_step: default_seek_step(),
+            forward_step: default_seek_step(),
+            log_level: default_log_level(),
+            logging: Default::default(),
+            thumbnail_cache_size: default_thumbnail_cache_size(),
+            search_backend: default_search_backend(),
+            shortcuts: BTreeMap::new(),
+            com: None,
+            refresh_token: None,
+            bot_link: None,
         }
     }
 }
 
 impl Config {
-    pub fn update(&self, path: PathBuf) {
-        //TODO Error Handling
+    /// Moves settings of old configs into a profile and makes sure the active profile exists
+    pub fn normalize(&mut self) {
+        if self.profiles.is_empty() {
+            let mut profile = Profile::default();
+            if let Some(com) = self.com.take() {
+                profile.com = com;
+            }
+            if let Some(bot_link) = self.bot_link.take() {
+                profile.bot_link = bot_link;
+            }
+            profile.refresh_token = self.refresh_token.take();
+            self.profiles.insert(self.active_profile.clone(), profile);
+        }
+        if !self.profiles.contains_key(&self.active_profile) {
+            if let Some(name) = self.profiles.keys().next() {
+                self.active_profile = name.clone();
+            }
+        }
+    }
+
+    /// Names of all profiles, separated by commas
+    pub fn profile_names(&self) -> String {
+        self.profiles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Creates, renames or deletes a profile, keeping the active profile valid
+    pub fn edit_profile(&mut self, edit: ProfileEdit) -> Result<(), String> {
+        match edit {
+            ProfileEdit::Create(name) => {
+                let name = self.free_profile_name(&name)?;
+                self.profiles.insert(name, Profile::default());
+            }
+            ProfileEdit::Rename(old, new) => {
+                let new = self.free_profile_name(&new)?;
+                let profile = self
+                    .profiles
+                    .remove(&old)
+                    .ok_or_else(|| format!("Unknown profile {}", old))?;
+                self.profiles.insert(new.clone(), profile);
+                if self.active_profile == old {
+                    self.active_profile = new;
+                }
+            }
+            ProfileEdit::Delete(name) => {
+                if self.profiles.len() <= 1 {
+                    return Err(String::from("The last profile can not be deleted"));
+                }
+                self.profiles
+                    .remove(&name)
+                    .ok_or_else(|| format!("Unknown profile {}", name))?;
+                self.normalize();
+            }
+        }
+        Ok(())
+    }
+
+    fn free_profile_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(String::from("Profile name is empty"));
+        }
+        if self.profiles.contains_key(name) {
+            return Err(format!("Profile {} already exists", name));
+        }
+        Ok(name.to_string())
+    }
+
+    pub fn profile(&self) -> &Profile {
+        self.profiles
+            .get(&self.active_profile)
+            .expect("Active profile missing, config not normalized")
+    }
+
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        self.profiles
+            .get_mut(&self.active_profile)
+            .expect("Active profile missing, config not normalized")
+    }
+
+    pub fn double_click_interval(&self) -> Duration {
+        Duration::from_millis(self.double_click_interval)
+    }
+
+    pub fn rewind_step(&self) -> Duration {
+        Duration::from_secs(self.rewind_step)
+    }
+
+    pub fn forward_step(&self) -> Duration {
+        Duration::from_secs(self.forward_step)
+    }
+
+    pub fn apply(&mut self, setting: Setting) {
+        match setting {
+            Setting::BotLink(link) => self.profile_mut().bot_link = link,
+            Setting::ClientId(id) => self.profile_mut().com.client_id = id,
+            Setting::AuthUrl(url) => self.profile_mut().com.auth_url = url,
+            Setting::RedirectUrl(url) => self.profile_mut().com.redirect_url = url,
+            Setting::DoubleClickInterval(ms) => self.double_click_interval = ms,
+            Setting::FontSize(size) => self.font_size = size,
+            Setting::RewindStep(secs) => self.rewind_step = secs,
+            Setting::ForwardStep(secs) => self.forward_step = secs,
+            Setting::LogLevel(level) => self.log_level = level,
+            Setting::ThumbnailCacheSize(size) => self.thumbnail_cache_size = size,
+            Setting::SearchBackend(url) => self.search_backend = url,
+        }
+    }
+
+    pub fn update(&self, path: PathBuf) -> Result<(), String> {
+        let bytes = serde_yaml::to_vec(self).map_err(|e| e.to_string())?;
         let mut file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(true)
             .open(path)
-            .unwrap();
+            .map_err(|e| e.to_string())?;
+
+        file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        file.write_all(bytes.as_slice()).map_err(|e| e.to_string())
+    }
+}
+
+/// A single validated change of the config
+#[derive(Debug, Clone, PartialEq)]
+pub enum Setting {
+    BotLink(String),
+    ClientId(String),
+    AuthUrl(String),
+    RedirectUrl(String),
+    DoubleClickInterval(u64),
+    FontSize(u16),
+    RewindStep(u64),
+    ForwardStep(u64),
+    LogLevel(LevelFilter),
+    ThumbnailCacheSize(usize),
+    SearchBackend(String),
+}
+
+/// Change of the set of profiles made by the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileEdit {
+    Create(String),
+    /// Old and new name
+    Rename(String, String),
+    Delete(String),
+}
+
+/// Values from the command line or environment, layered over the config loaded from disk
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    profile: Option<String>,
+    bot_link: Option<String>,
+    theme: Option<Theme>,
+    log_level: Option<LevelFilter>,
+    log_modules: Vec<(String, LevelFilter)>,
+    log_json: bool,
+    persist: bool,
+    original: Option<Box<Config>>,
+    /// Profile the bot link override was applied to
+    overridden_profile: Option<String>,
+}
 
-        file.seek(SeekFrom::Start(0)).unwrap();
-        file.write_all(serde_yaml::to_vec(self).unwrap().as_slice())
-            .unwrap();
+impl ConfigOverrides {
+    pub fn new(
+        profile: Option<String>,
+        bot_link: Option<String>,
+        theme: Option<Theme>,
+        log_level: Option<LevelFilter>,
+        log_modules: Vec<(String, LevelFilter)>,
+        log_json: bool,
+        persist: bool,
+    ) -> Self {
+        ConfigOverrides {
+            profile,
+            bot_link,
+            theme,
+            log_level,
+            log_modules,
+            log_json,
+            persist,
+            original: None,
+            overridden_profile: None,
+        }
+    }
+
+    /// Applies the overrides, remembering the replaced values for [`ConfigOverrides::strip`]
+    pub fn apply(&mut self, cfg: &mut Config) {
+        self.original = Some(Box::new(cfg.clone()));
+        if let Some(profile) = self.profile.as_ref() {
+            if cfg.profiles.contains_key(profile) {
+                cfg.active_profile = profile.clone();
+            }
+        }
+        if let Some(bot_link) = self.bot_link.as_ref() {
+            cfg.profile_mut().bot_link = bot_link.clone();
+            self.overridden_profile = Some(cfg.active_profile.clone());
+        }
+        if let Some(theme) = self.theme {
+            cfg.theme = theme;
+        }
+        if let Some(level) = self.log_level {
+            cfg.log_level = level;
+        }
+        for (module, level) in self.log_modules.iter() {
+            cfg.logging.modules.insert(module.clone(), *level);
+        }
+        if self.log_json {
+            cfg.logging.json = true;
+        }
+    }
+
+    /// Fails if the profile to use does not exist in the config
+    pub fn check(&self, cfg: &Config) -> Result<(), String> {
+        match self.profile.as_ref() {
+            Some(profile) if !cfg.profiles.contains_key(profile) => Err(format!(
+                "Unknown profile {}, existing profiles: {}",
+                profile,
+                cfg.profile_names()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the config as it should be written to disk
+    pub fn strip(&self, cfg: &Config) -> Config {
+        let mut cfg = cfg.clone();
+        if self.persist {
+            return cfg;
+        }
+        if let Some(original) = self.original.as_ref() {
+            if self.profile.is_some() && cfg.profiles.contains_key(&original.active_profile) {
+                cfg.active_profile = original.active_profile.clone();
+            }
+            if let Some(name) = self.overridden_profile.as_ref() {
+                let bot_link = original
+                    .profiles
+                    .get(name)
+                    .map(|p| p.bot_link.clone())
+                    .unwrap_or_else(default_bot_link);
+                if let Some(profile) = cfg.profiles.get_mut(name) {
+                    profile.bot_link = bot_link;
+                }
+            }
+            if self.theme.is_some() {
+                cfg.theme = original.theme;
+            }
+            if self.log_level.is_some() {
+                cfg.log_level = original.log_level;
+            }
+            //Other logging settings may have been changed in the app meanwhile
+            for (module, _) in self.log_modules.iter() {
+                match original.logging.modules.get(module) {
+                    Some(level) => cfg.logging.modules.insert(module.clone(), *level),
+                    None => cfg.logging.modules.remove(module),
+                };
+            }
+            if self.log_json {
+                cfg.logging.json = original.logging.json;
+            }
+        }
+        cfg
+    }
+
+    /// Forgets the override of the theme, used once the user picks a theme explicitly
+    pub fn clear_theme(&mut self) {
+        self.theme = None;
+    }
+
+    /// Forgets the override of the profile, used once the user picks a profile explicitly
+    pub fn clear_profile(&mut self) {
+        self.profile = None;
+    }
+
+    /// Follows a profile renamed by the user
+    pub fn rename_profile(&mut self, old: &str, new: &str) {
+        if let Some(original) = self.original.as_mut() {
+            if let Some(profile) = original.profiles.remove(old) {
+                original.profiles.insert(new.to_string(), profile);
+            }
+            if original.active_profile == old {
+                original.active_profile = new.to_string();
+            }
+        }
+        if self.overridden_profile.as_deref() == Some(old) {
+            self.overridden_profile = Some(new.to_string());
+        }
+    }
+
+    /// Forgets the overrides a setting changed by the user replaces
+    pub fn clear_setting(&mut self, setting: &Setting) {
+        match setting {
+            Setting::BotLink(_) => self.overridden_profile = None,
+            Setting::LogLevel(_) => self.log_level = None,
+            _ => {}
+        }
     }
 }
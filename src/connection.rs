@@ -1,19 +1,32 @@
+use crate::error::Error;
 use crate::Message as CrateMessage;
 use async_tungstenite::tokio::{connect_async, ConnectStream};
 use async_tungstenite::WebSocketStream;
 use futures::stream::{SplitSink, SplitStream};
 use iced::futures::{SinkExt, StreamExt};
 use iced::Command;
+use log::debug;
 use reciprocity_communication::messages::oauth2::RefreshToken;
 use reciprocity_communication::messages::{Auth, AuthMessage, ClientRequest, Message, User};
+use std::fmt::{Display, Formatter};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tungstenite::Message as TungMessage;
 
 #[derive(Debug, Clone)]
 pub struct Connection {
+    id: uuid::Uuid,
     send: Arc<Mutex<SplitSink<WebSocketStream<ConnectStream>, TungMessage>>>,
     rec: Arc<Mutex<SplitStream<WebSocketStream<ConnectStream>>>>,
+    latency: Arc<std::sync::Mutex<Latency>>,
+}
+
+/// Round trip of websocket pings, to estimate how old received player positions are
+#[derive(Debug, Default)]
+struct Latency {
+    ping_sent: Option<Instant>,
+    round_trip: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +35,19 @@ pub enum ConnectionError {
     RmpSerdeEncode(Arc<rmp_serde::encode::Error>),
     RmpSerdeDecode(Arc<rmp_serde::decode::Error>),
     NonAuthMessage(Box<Message>),
+    Closed,
+}
+
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionError::Tungstenite(e) => write!(f, "{}", e),
+            ConnectionError::RmpSerdeEncode(e) => write!(f, "Could not encode message: {}", e),
+            ConnectionError::RmpSerdeDecode(e) => write!(f, "Could not decode message: {}", e),
+            ConnectionError::NonAuthMessage(_) => write!(f, "Bot did not accept authentication"),
+            ConnectionError::Closed => write!(f, "Connection closed"),
+        }
+    }
 }
 
 impl From<tungstenite::Error> for ConnectionError {
@@ -56,7 +82,7 @@ impl Connection {
         let (mut socket, _) = connect_async(bot_http).await?;
         let auth_msg = Message::ClientRequest(ClientRequest::Authenticate(auth)).generate()?;
         socket.send(TungMessage::Binary(auth_msg)).await?;
-        let resp = socket.next().await.expect("None Error expected")?;
+        let resp = socket.next().await.ok_or(ConnectionError::Closed)??;
         let resp = resp.into_data();
         let msg = Message::parse(resp.as_slice())?;
         let (user, token) = if let Message::Auth(AuthMessage::AuthSuccess(user, token)) = msg {
@@ -68,18 +94,71 @@ impl Connection {
 
         Ok((
             Connection {
+                id: uuid::Uuid::new_v4(),
                 send: Arc::new(Mutex::new(send)),
                 rec: Arc::new(Mutex::new(rec)),
+                latency: Default::default(),
             },
             (user, token),
         ))
     }
 
+    pub fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+
     pub async fn receive(self) -> Result<Message, ConnectionError> {
         let mut rec_lock = self.rec.lock().await;
-        let msg = rec_lock.next().await.expect("None Error expected")?;
-        let msg = msg.into_data();
-        Message::parse(msg.as_slice()).map_err(|e| e.into())
+        loop {
+            match rec_lock.next().await.ok_or(ConnectionError::Closed)?? {
+                //Answered by tungstenite itself
+                TungMessage::Ping(_) => {}
+                TungMessage::Pong(_) => self.pong_received(),
+                msg => {
+                    let msg = msg.into_data();
+                    return Message::parse(msg.as_slice()).map_err(|e| e.into());
+                }
+            }
+        }
+    }
+
+    pub async fn ping(self) -> Result<(), ConnectionError> {
+        let mut send_lock = self.send.lock().await;
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.ping_sent = Some(Instant::now());
+        }
+        send_lock
+            .send(TungMessage::Ping(Vec::new()))
+            .await
+            .map_err(|e| e.into())
+    }
+
+    fn pong_received(&self) {
+        if let Ok(mut latency) = self.latency.lock() {
+            if let Some(sent) = latency.ping_sent.take() {
+                latency.round_trip = Some(sent.elapsed());
+            }
+        }
+    }
+
+    /// Estimated time a message of the bot takes to arrive
+    pub fn latency(&self) -> Duration {
+        self.latency
+            .lock()
+            .ok()
+            .and_then(|l| l.round_trip)
+            .map(|rtt| rtt / 2)
+            .unwrap_or_default()
+    }
+
+    pub fn get_ping_cmd(&self) -> Command<CrateMessage> {
+        Command::perform(self.clone().ping(), |res| {
+            //A broken connection is reported by the receive chain
+            if let Err(e) = res {
+                debug!("Could not send ping: {}", e);
+            }
+            CrateMessage::None()
+        })
     }
 
     pub async fn send(self, req: ClientRequest) -> Result<(), ConnectionError> {
@@ -92,9 +171,16 @@ impl Connection {
             .map_err(|e| e.into())
     }
 
+    /// Closes the socket, ending the receive chain of this connection
+    pub async fn close(self) -> Result<(), ConnectionError> {
+        let mut send_lock = self.send.lock().await;
+        send_lock.close().await.map_err(|e| e.into())
+    }
+
     pub fn get_rec_cmd(&self) -> Command<CrateMessage> {
-        Command::perform(self.clone().receive(), |res| {
-            CrateMessage::ReceiveBotMessage(res)
+        let id = self.id;
+        Command::perform(self.clone().receive(), move |res| {
+            CrateMessage::ReceiveBotMessage(id, res)
         })
     }
 
@@ -102,12 +188,11 @@ impl Connection {
         &self,
         req: reciprocity_communication::messages::PlayerControl,
     ) -> Command<crate::Message> {
-        println!("Request: {:?}", req);
-        Command::perform(self.clone().send(ClientRequest::Control(uuid::Uuid::new_v4().to_string(), req)), |res| {
-            if let Err(e) = res {
-                panic!("Error sending request. {:?}", e)
-            }
-            crate::Message::None()
+        debug!("Sending control request={:?}", req);
+        let request = ClientRequest::Control(uuid::Uuid::new_v4().to_string(), req.clone());
+        Command::perform(self.clone().send(request), move |res| match res {
+            Ok(_) => crate::Message::None(),
+            Err(e) => crate::Message::Error(Error::Request(req.clone(), e)),
         })
     }
 }
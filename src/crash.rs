@@ -0,0 +1,131 @@
+use crate::config::Config;
+use crate::log::{recent_records, redact};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use reciprocity_communication::messages::PlayerState;
+use std::backtrace::Backtrace;
+use std::fmt::{Display, Write as FmtWrite};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Number of log records included in a crash report
+pub const CRASH_LOG_RECORDS: usize = 200;
+const CRASH_DIR: &str = "crashes";
+/// Contains the path of a report the user was not told about yet
+const PENDING_FILE: &str = "pending";
+
+/// State of the app at the time of the last update, written into crash reports
+#[derive(Debug, Default)]
+struct CrashContext {
+    connection: String,
+    config: Option<Config>,
+    player_state: Option<PlayerState>,
+}
+
+static CRASH_CONTEXT: Lazy<Mutex<CrashContext>> = Lazy::new(|| {
+    Mutex::new(CrashContext {
+        connection: String::from("Not connected"),
+        ..Default::default()
+    })
+});
+
+pub fn set_connection(state: String) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.connection = state;
+    }
+}
+
+/// Description of the connection as last reported
+pub fn connection_state() -> String {
+    CRASH_CONTEXT
+        .lock()
+        .map(|c| c.connection.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(cfg: &Config) {
+    let mut cfg = cfg.clone();
+    for profile in cfg.profiles.values_mut() {
+        profile.refresh_token = None;
+    }
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.config = Some(cfg);
+    }
+}
+
+pub fn set_player_state(state: Option<&PlayerState>) {
+    if let Ok(mut context) = CRASH_CONTEXT.lock() {
+        context.player_state = state.cloned();
+    }
+}
+
+/// Writes a crash report into the data dir before the default panic output
+pub fn install_hook(data_dir: &Path) {
+    let crash_dir = data_dir.join(CRASH_DIR);
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        //The report comes first, logging goes through locks the panicking code may hold
+        match write_report(&crash_dir, &info) {
+            Ok(path) => ::log::error!("Crashed, report written to {:?}", path),
+            Err(e) => ::log::error!("Crashed, could not write report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_report(crash_dir: &Path, info: &dyn Display) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(crash_dir)?;
+    let path = crash_dir.join(Local::now().format("crash %Y-%m-%d %H_%M_%S.txt").to_string());
+    let mut file = File::create(&path)?;
+    file.write_all(report(info).as_bytes())?;
+    std::fs::write(
+        crash_dir.join(PENDING_FILE),
+        path.to_string_lossy().as_bytes(),
+    )?;
+    Ok(path)
+}
+
+fn report(info: &dyn Display) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "Reciprocity Companion {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {}", Local::now().to_rfc3339());
+    let _ = writeln!(report, "\n== Panic ==\n{}", info);
+    let _ = writeln!(report, "\n== Backtrace ==\n{}", Backtrace::force_capture());
+
+    //The panic may have happened while the context was locked, so never wait for it
+    match CRASH_CONTEXT.try_lock() {
+        Ok(context) => {
+            let _ = writeln!(report, "\n== Connection ==\n{}", context.connection);
+            let config = context
+                .config
+                .as_ref()
+                .map(|c| serde_yaml::to_string(c).unwrap_or_else(|e| e.to_string()))
+                .unwrap_or_else(|| String::from("None"));
+            let _ = writeln!(report, "\n== Config ==\n{}", redact(&config));
+            let _ = writeln!(
+                report,
+                "\n== Player State ==\n{}",
+                redact(&format!("{:#?}", context.player_state))
+            );
+        }
+        Err(_) => {
+            let _ = writeln!(report, "\n== State ==\nUnavailable");
+        }
+    }
+
+    let _ = writeln!(report, "\n== Log ==");
+    for record in recent_records(CRASH_LOG_RECORDS) {
+        let _ = writeln!(report, "{}", record);
+    }
+    report
+}
+
+/// Returns the report of a crash the user was not told about yet, forgetting it
+pub fn take_pending_report(data_dir: &Path) -> Option<PathBuf> {
+    let pending = data_dir.join(CRASH_DIR).join(PENDING_FILE);
+    let path = std::fs::read_to_string(&pending).ok()?;
+    let _ = std::fs::remove_file(&pending);
+    Some(PathBuf::from(path)).filter(|p| p.exists())
+}
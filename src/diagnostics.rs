@@ -0,0 +1,161 @@
+use crate::config::Config;
+use crate::log::{buffered_records, log_files, redact, LogRecord};
+use chrono::Local;
+use iced_wgpu::wgpu;
+use reciprocity_communication::messages::PlayerState;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Number of log files, newest first, put into a diagnostic bundle
+pub const DIAGNOSTIC_LOG_FILES: usize = 3;
+pub const DIAGNOSTIC_EXTENSIONS: [&str; 1] = ["zip"];
+
+/// Everything of the running app which goes into a diagnostic bundle
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    log_dir: PathBuf,
+    config: String,
+    window_size: (u32, u32),
+    font_size: u16,
+    session: Option<SessionCapture>,
+}
+
+/// What happened since the start, only included if the user asks for it
+#[derive(Debug, Clone)]
+pub struct SessionCapture {
+    connection: String,
+    player_state: String,
+    records: Vec<LogRecord>,
+}
+
+impl SessionCapture {
+    pub fn new(connection: String, player_state: Option<&PlayerState>) -> Self {
+        SessionCapture {
+            connection,
+            player_state: redact(&format!("{:#?}", player_state)),
+            records: buffered_records(),
+        }
+    }
+}
+
+impl Diagnostics {
+    pub fn new(
+        cfg: &Config,
+        log_dir: PathBuf,
+        window_size: (u32, u32),
+        session: Option<SessionCapture>,
+    ) -> Self {
+        let mut cfg = cfg.clone();
+        for profile in cfg.profiles.values_mut() {
+            profile.refresh_token = None;
+        }
+        let config = serde_yaml::to_string(&cfg).unwrap_or_else(|e| e.to_string());
+        Diagnostics {
+            log_dir,
+            config: redact(&config),
+            window_size,
+            font_size: cfg.font_size,
+            session,
+        }
+    }
+
+    /// Zips everything into the file at the path
+    pub async fn save(self, path: PathBuf) -> Result<PathBuf, String> {
+        let system = self.system_info().await;
+        let file =
+            File::create(&path).map_err(|e| format!("Could not create {:?}: {}", path, e))?;
+        self.write_zip(file, system)
+            .map_err(|e| format!("Could not write {:?}: {}", path, e))?;
+        Ok(path)
+    }
+
+    fn write_zip(&self, file: File, system: String) -> zip::result::ZipResult<()> {
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut zip = ZipWriter::new(file);
+
+        zip.start_file("system.txt", options)?;
+        zip.write_all(system.as_bytes())?;
+        zip.start_file("config.yml", options)?;
+        zip.write_all(self.config.as_bytes())?;
+
+        for log in log_files(&self.log_dir).iter().take(DIAGNOSTIC_LOG_FILES) {
+            if let Some(name) = log.file_name() {
+                zip.start_file(format!("logs/{}", name.to_string_lossy()), options)?;
+                zip.write_all(&read_or_error(log))?;
+            }
+        }
+
+        if let Some(session) = self.session.as_ref() {
+            zip.start_file("session/connection.txt", options)?;
+            zip.write_all(session.connection.as_bytes())?;
+            zip.start_file("session/player_state.txt", options)?;
+            zip.write_all(session.player_state.as_bytes())?;
+            zip.start_file("session/log.txt", options)?;
+            for record in session.records.iter() {
+                writeln!(zip, "{}", record)?;
+            }
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    async fn system_info(&self) -> String {
+        let mut info = String::new();
+        let _ = writeln!(info, "Created: {}", Local::now().to_rfc3339());
+        let _ = writeln!(info, "App Version: {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(
+            info,
+            "Build: {}",
+            match cfg!(debug_assertions) {
+                true => "debug",
+                false => "release",
+            }
+        );
+        //Resolved from the lockfile by the build script
+        let _ = writeln!(info, "Protocol: {}", env!("PROTOCOL_SOURCE"));
+        let _ = writeln!(info, "OS: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+        let _ = writeln!(info, "Window Size: {}x{}", self.window_size.0, self.window_size.1);
+        let _ = writeln!(info, "Font Size: {}", self.font_size);
+        let _ = writeln!(info, "Default Adapter: {}", default_adapter_info().await);
+        info
+    }
+}
+
+/// Adapter wgpu picks by default.
+/// iced does not expose the adapter it renders with, which may be a different one.
+async fn default_adapter_info() -> String {
+    let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: Default::default(),
+            compatible_surface: None,
+        })
+        .await;
+    match adapter {
+        Some(adapter) => {
+            let info = adapter.get_info();
+            format!(
+                "wgpu {:?} on {} ({:?}, vendor {:#x}, device {:#x})",
+                info.backend, info.name, info.device_type, info.vendor, info.device
+            )
+        }
+        None => String::from("wgpu, no adapter found"),
+    }
+}
+
+fn read_or_error(path: &Path) -> Vec<u8> {
+    std::fs::read(path)
+        .unwrap_or_else(|e| format!("Could not read {:?}: {}", path, e).into_bytes())
+}
+
+/// Default name of a bundle, so several of them do not overwrite each other
+pub fn file_name() -> String {
+    Local::now()
+        .format("reciprocity_diagnostics %Y-%m-%d %H_%M_%S.zip")
+        .to_string()
+}
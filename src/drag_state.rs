@@ -0,0 +1,127 @@
+use iced::Point;
+use reciprocity_communication::messages::Track;
+use std::time::Duration;
+
+/// Pixels the cursor has to move with the button held before a press becomes a drag
+const DRAG_THRESHOLD: f32 = 8.0;
+/// Part of the list at its top and bottom that scrolls it while dragging over it
+const SCROLL_EDGE: f32 = 0.1;
+/// Pixels scrolled per step at the very edge of the list
+const SCROLL_STEP: f32 = 20.0;
+pub const SCROLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What is dragged
+#[derive(Debug, Clone)]
+pub enum DragItem {
+    /// Track in the queue, by index
+    Queued(usize),
+    /// Track of the open saved playlist, by index
+    Saved(usize),
+    /// Track from anywhere else
+    Track(Track),
+}
+
+#[derive(Debug, Clone)]
+pub enum DragMessage {
+    Pressed(DragItem),
+    Moved(Point),
+    Released(),
+    /// Cursor is over the entry at the index of the shown list, at the fraction of its height
+    OverEntry(usize, f32),
+    /// Cursor is over the list, at the fraction of its height, which is the second value
+    OverList(f32, f32),
+    LeftList(),
+    AutoScroll(),
+}
+
+/// Drag and drop of tracks into the queue or a saved playlist, across tabs
+#[derive(Debug, Default)]
+pub struct Drag {
+    item: Option<DragItem>,
+    /// Where the cursor first moved after the press
+    origin: Option<Point>,
+    active: bool,
+    /// Index in the shown list the item is dropped at
+    target: Option<usize>,
+    /// Pixels to scroll the list per step, negative for up
+    scroll: f32,
+    list_height: f32,
+}
+
+impl Drag {
+    /// True from the press on, so the cursor has to be followed
+    pub fn is_pressed(&self) -> bool {
+        self.item.is_some()
+    }
+
+    /// True once the cursor moved far enough for a drag
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The dragged item once the drag is active
+    pub fn item(&self) -> Option<&DragItem> {
+        self.item.as_ref().filter(|_| self.active)
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        self.target
+    }
+
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    pub fn list_height(&self) -> f32 {
+        self.list_height
+    }
+
+    /// Returns the item and the target when it was dropped on a list
+    pub fn update(&mut self, message: DragMessage) -> Option<(DragItem, usize)> {
+        match message {
+            DragMessage::Pressed(item) => {
+                *self = Drag::default();
+                self.item = Some(item);
+            }
+            DragMessage::Moved(pos) => {
+                if self.item.is_some() && !self.active {
+                    let origin = *self.origin.get_or_insert(pos);
+                    let (dx, dy) = (pos.x - origin.x, pos.y - origin.y);
+                    self.active = (dx * dx + dy * dy).sqrt() >= DRAG_THRESHOLD;
+                }
+            }
+            DragMessage::Released() => {
+                let drag = std::mem::take(self);
+                if drag.active {
+                    return drag.item.zip(drag.target);
+                }
+            }
+            DragMessage::OverEntry(i, fraction) => {
+                if self.active {
+                    self.target = match fraction < 0.5 {
+                        true => Some(i),
+                        false => Some(i + 1),
+                    };
+                }
+            }
+            DragMessage::OverList(fraction, height) => {
+                if self.active {
+                    self.list_height = height;
+                    //Faster the closer the cursor is to the edge
+                    self.scroll = match fraction {
+                        f if f < SCROLL_EDGE => -SCROLL_STEP * (1.0 - f / SCROLL_EDGE),
+                        f if f > 1.0 - SCROLL_EDGE => SCROLL_STEP * (1.0 - (1.0 - f) / SCROLL_EDGE),
+                        _ => 0.0,
+                    };
+                }
+            }
+            DragMessage::LeftList() => {
+                self.target = None;
+                self.scroll = 0.0;
+            }
+            //Scrolling is up to the list
+            DragMessage::AutoScroll() => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,63 @@
+use crate::connection::ConnectionError;
+use crate::tabs::library::LibraryMessage;
+use crate::tabs::search::SearchMessage;
+use crate::util::youtube::SearchError;
+use crate::Message;
+use iced::Command;
+use reciprocity_communication::client::OAuthError;
+use reciprocity_communication::messages::PlayerControl;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Every recoverable failure of the companion, shown to the user as a banner
+#[derive(Debug, Clone)]
+pub enum Error {
+    Connect(ConnectionError),
+    ConnectionLost(ConnectionError),
+    Auth(OAuthError),
+    Request(PlayerControl, ConnectionError),
+    Search(String, SearchError),
+    Image(String, String),
+    InvalidUrl(String),
+    SaveConfig(PathBuf, String),
+    LoadLibrary(PathBuf, String),
+    SaveLibrary(PathBuf, String),
+}
+
+impl Error {
+    /// Message which tries the failed action again, if that could help
+    pub fn retry(&self) -> Option<Message> {
+        match self {
+            Error::Connect(_) | Error::ConnectionLost(_) | Error::Auth(_) => {
+                Some(Message::Reconnect())
+            }
+            Error::Request(req, _) => Some(Message::SendControl(req.clone())),
+            Error::Search(query, _) => Some(Message::Search(SearchMessage::Search(query.clone()))),
+            Error::SaveConfig(..) => Some(Message::SaveConfig()),
+            Error::SaveLibrary(..) => Some(Message::Library(LibraryMessage::Save())),
+            Error::Image(..) | Error::InvalidUrl(_) | Error::LoadLibrary(..) => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Connect(e) => write!(f, "Could not connect to the bot: {}", e),
+            Error::ConnectionLost(e) => write!(f, "Lost the connection to the bot: {}", e),
+            Error::Auth(e) => write!(f, "Authentication failed: {:?}", e),
+            Error::Request(req, e) => write!(f, "Could not send {:?}: {}", req, e),
+            Error::Search(query, e) => write!(f, "Search for \"{}\" failed: {}", query, e),
+            Error::Image(url, e) => write!(f, "Could not load image {}: {}", url, e),
+            Error::InvalidUrl(url) => write!(f, "Invalid url: {}", url),
+            Error::SaveConfig(p, e) => write!(f, "Could not save config {:?}: {}", p, e),
+            Error::LoadLibrary(p, e) => write!(f, "Could not load playlists {:?}: {}", p, e),
+            Error::SaveLibrary(p, e) => write!(f, "Could not save playlists {:?}: {}", p, e),
+        }
+    }
+}
+
+/// Command reporting the error to the companion
+pub fn report(e: Error) -> Command<Message> {
+    Command::perform(futures::future::ready(e), Message::Error)
+}
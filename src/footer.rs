@@ -32,6 +32,14 @@ impl PlayerFooter {
         Command::none()
     }
 
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn voice_channel(&self) -> Option<&str> {
+        self.voice_channel.as_deref()
+    }
+
     pub fn view(&mut self, theme: &Theme) -> Element<'_, Message> {
         let mut row = Row::new();
 
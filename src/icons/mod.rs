@@ -20,6 +20,11 @@ pub enum Icon {
     Error,
     Warning,
     Settings,
+    Subject,
+    List,
+    FastRewind,
+    FastForward,
+    LibraryMusic,
 }
 
 impl Icon {
@@ -42,6 +47,11 @@ impl Icon {
                 Icon::History => include_bytes!("google/black/history.svg"),
                 Icon::Search => include_bytes!("google/black/search.svg"),
                 Icon::Settings => include_bytes!("google/black/settings.svg"),
+                Icon::Subject => include_bytes!("google/black/subject.svg"),
+                Icon::List => include_bytes!("google/black/list.svg"),
+                Icon::FastRewind => include_bytes!("google/black/fast-rewind.svg"),
+                Icon::FastForward => include_bytes!("google/black/fast-forward.svg"),
+                Icon::LibraryMusic => include_bytes!("google/black/library-music.svg"),
             },
             Theme::Dark => match self {
                 Icon::Eject => include_bytes!("google/white/eject.svg"),
@@ -60,6 +70,11 @@ impl Icon {
                 Icon::History => include_bytes!("google/white/history.svg"),
                 Icon::Search => include_bytes!("google/white/search.svg"),
                 Icon::Settings => include_bytes!("google/white/settings.svg"),
+                Icon::Subject => include_bytes!("google/white/subject.svg"),
+                Icon::List => include_bytes!("google/white/list.svg"),
+                Icon::FastRewind => include_bytes!("google/white/fast-rewind.svg"),
+                Icon::FastForward => include_bytes!("google/white/fast-forward.svg"),
+                Icon::LibraryMusic => include_bytes!("google/white/library-music.svg"),
             },
         }
     }
@@ -1,45 +1,116 @@
 #![allow(dead_code)]
 
+mod banner;
+mod bundle;
+pub mod cli;
+mod clock;
 pub mod config;
 mod connection;
+pub mod crash;
+mod diagnostics;
+mod drag_state;
+mod error;
 mod executor;
 mod footer;
 pub mod icons;
+mod library;
 mod player_control;
-mod states;
+mod playlist_file;
+mod recovery;
+mod shortcuts;
+pub mod startup;
+pub mod states;
 mod tabs;
 mod theme;
+mod toast;
 pub mod util;
 mod log;
 
-use crate::config::Config;
+use crate::banner::{BannerMessage, Banners};
+use crate::bundle::{BundleMessage, ImportMode, SettingsBundle, BUNDLE_EXTENSIONS};
+use crate::config::{Config, ConfigOverrides, ProfileEdit, Setting};
 use crate::connection::{Connection, ConnectionError};
+use crate::diagnostics::{Diagnostics, SessionCapture, DIAGNOSTIC_EXTENSIONS};
+use crate::drag_state::{Drag, DragItem, DragMessage, SCROLL_INTERVAL};
+use crate::error::Error;
 use crate::footer::{FooterMessage, PlayerFooter};
-use crate::player_control::{PlayerControl, PlayerControlMessage};
+use crate::library::Library;
+use crate::player_control::{ButtonEvent, PlayerControl, PlayerControlMessage};
+use crate::recovery::{RecoveryMessage, RecoveryView};
+use crate::shortcuts::{Action, ShortcutMessage, Shortcuts, LONG_SEEK_SECS, SHORT_SEEK_SECS};
+use crate::startup::StartupError;
+use crate::states::{ScrollOffsets, TabKey, UiState};
+use crate::tabs::activity::{ActivityMessage, ActivityTab};
 use crate::tabs::history::{HistoryMessage, HistoryTab};
+use crate::tabs::library::{LibraryMessage, LibraryTab, ENQUEUE_INTERVAL};
+use crate::tabs::logs::{LogsMessage, LogsTab};
 use crate::tabs::playlist::{PlaylistMessage, PlaylistTab};
 use crate::tabs::search::{SearchMessage, SearchTab};
 use crate::tabs::settings::{SettingsMessage, SettingsTab};
 use crate::tabs::{Tab, Tabs};
 use crate::theme::Theme;
-use iced::{Application, Clipboard, Column, Command, Container, Element, Length, Row};
+use crate::toast::{Toast, ToastMessage, Toasts};
+use iced::{
+    Application, Clipboard, Column, Command, Container, Element, Length, Row, Subscription,
+};
 use reciprocity_communication::client::{get_auth_code, OAuthError};
 use reciprocity_communication::messages::oauth2::{AuthorizationCode, RefreshToken};
-use reciprocity_communication::messages::{Auth, User, PlayerControlResult};
-use reciprocity_communication::messages::{Message as ComMessage, PlayerState, State};
+use reciprocity_communication::messages::PlayerControl as ControlRequest;
+use reciprocity_communication::messages::{Auth, User};
+use reciprocity_communication::messages::{Message as ComMessage, PlayerState, State, Track};
+use ::log::{debug, error, info, trace, warn};
 use std::ops::Deref;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
-use crate::log::LogMessage;
+
+/// Index of the Playlist tab, opened by clicking track and enqueue notices
+const PLAYLIST_TAB: usize = 0;
+const HISTORY_TAB: usize = 1;
+const SEARCH_TAB: usize = 2;
+const LIBRARY_TAB: usize = 3;
+const ACTIVITY_TAB: usize = 4;
+/// Index of the Logs tab, which refreshes while it is shown
+const LOGS_TAB: usize = 5;
+const SETTINGS_TAB: usize = 6;
+/// Keys the tabs are stored by, in the order of their indices
+const TAB_KEYS: [TabKey; 7] = [
+    TabKey::Playlist,
+    TabKey::History,
+    TabKey::Search,
+    TabKey::Library,
+    TabKey::Activity,
+    TabKey::Logs,
+    TabKey::Settings,
+];
+
+/// Interval of moving the position slider along while playing
+const TICK_INTERVAL: Duration = Duration::from_millis(200);
+/// Interval of measuring the latency to the bot
+const PING_INTERVAL: Duration = Duration::from_secs(10);
 
 pub const MAX_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(300);
 
+static DOUBLE_CLICK_INTERVAL: AtomicU64 = AtomicU64::new(300);
+
+/// Maximum time between two clicks to count as a double click, as configured
+pub fn double_click_interval() -> Duration {
+    Duration::from_millis(DOUBLE_CLICK_INTERVAL.load(Ordering::Relaxed))
+}
+
+fn set_double_click_interval(interval: Duration) {
+    DOUBLE_CLICK_INTERVAL.store(interval.as_millis() as u64, Ordering::Relaxed);
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     None(),
-    GotAuth(Result<AuthorizationCode, OAuthError>),
-    GotConnection(Result<(Connection, (User, RefreshToken)), ConnectionError>),
-    ReceiveBotMessage(Result<ComMessage, ConnectionError>),
+    GotAuth(String, Result<AuthorizationCode, OAuthError>),
+    GotConnection(
+        String,
+        Result<(Connection, (User, RefreshToken)), ConnectionError>,
+    ),
+    ReceiveBotMessage(uuid::Uuid, Result<ComMessage, ConnectionError>),
 
     PlayerControl(PlayerControlMessage),
     Footer(FooterMessage),
@@ -47,120 +118,692 @@ pub enum Message {
     Playlist(PlaylistMessage),
     History(HistoryMessage),
     Search(SearchMessage),
+    Library(LibraryMessage),
+    Activity(ActivityMessage),
+    Logs(LogsMessage),
     Settings(SettingsMessage),
+    Recovery(RecoveryMessage),
+    Bundle(BundleMessage),
+    Banner(BannerMessage),
+    Error(Error),
+    ShowToast(Toast),
+    Toast(ToastMessage),
+    Shortcut(ShortcutMessage),
+    Drag(DragMessage),
 
     ThemeChanged(Theme),
+    ProfileSelected(String),
+    ProfileEdited(ProfileEdit),
+    /// Opens the library to pick the playlist the track is saved to
+    AddToPlaylist(Track),
+    SettingChanged(Setting),
+    Reconnect(),
+    Ping(),
+    SendControl(ControlRequest),
+    SaveConfig(),
     TabSelected(usize),
+    WindowResized(u32, u32),
+    CloseRequested(),
+}
+
+#[derive(Debug, Clone)]
+pub struct Flags {
+    pub config: Config,
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub overrides: ConfigOverrides,
+    pub no_auth: bool,
+    pub startup_errors: Vec<StartupError>,
+    /// Report of a crash during the previous run
+    pub crash_report: Option<PathBuf>,
+    pub ui_state: UiState,
 }
 
 #[derive(Debug)]
 pub struct Companion {
     cfg: Config,
     cfg_path: PathBuf,
+    cfg_overrides: ConfigOverrides,
+    /// False if the config on disk is broken and must not be overwritten
+    cfg_writable: bool,
+    data_dir: PathBuf,
+    no_auth: bool,
+    window_size: (u32, u32),
+    /// Text size the window was opened with, a changed font size scales the window instead
+    launch_font_size: u16,
+    should_exit: bool,
     theme: theme::Theme,
     connection: Option<Connection>,
     player_state: Option<PlayerState>,
 
-    app_log: Vec<LogMessage>,
-    control_log: Vec<PlayerControlResult>,
-
     player_control: PlayerControl,
     footer: PlayerFooter,
+    recovery: RecoveryView,
+    banners: Banners,
+    toasts: Toasts,
+    shortcuts: Shortcuts,
+    drag: Drag,
+    pending_import: Option<SettingsBundle>,
 
-    tabs: Tabs<Message, 4>,
+    tabs: Tabs<Message, 7>,
     playlist_tab: PlaylistTab,
     history_tab: HistoryTab,
     search_tab: SearchTab,
+    library_tab: LibraryTab,
+    activity_tab: ActivityTab,
+    logs_tab: LogsTab,
     settings_tab: SettingsTab,
 }
 
+impl Companion {
+    /// Writes the config to disk, leaving out command line and environment overrides
+    fn save_config(&mut self) {
+        crash::set_config(&self.cfg);
+        if !self.cfg_writable {
+            return;
+        }
+        if let Err(e) = self
+            .cfg_overrides
+            .strip(&self.cfg)
+            .update(self.cfg_path.clone())
+        {
+            self.fail(Error::SaveConfig(self.cfg_path.clone(), e));
+        }
+    }
+
+    /// Logs the error and shows it as a banner
+    fn fail(&mut self, e: Error) {
+        error!("{}", e);
+        self.banners.push(e);
+    }
+
+    fn connect(&self) -> Command<Message> {
+        if self.no_auth {
+            info!("Skipping authentication, profile={}", self.cfg.active_profile);
+            Command::none()
+        } else if let Some(token) = self.cfg.profile().refresh_token.as_ref() {
+            debug!("Connecting with refresh token, profile={}", self.cfg.active_profile);
+            self.connect_with(Auth::Token(token.clone()))
+        } else {
+            debug!("Requesting auth code, profile={}", self.cfg.active_profile);
+            self.request_auth_code()
+        }
+    }
+
+    /// Connects to the bot of the active profile, tagging the result with the profile name
+    fn connect_with(&self, auth: Auth) -> Command<Message> {
+        let profile = self.cfg.active_profile.clone();
+        crash::set_connection(format!(
+            "Connecting to {} with profile {}",
+            self.cfg.profile().bot_link,
+            profile
+        ));
+        Command::perform(
+            Connection::new(auth, self.cfg.profile().bot_link.clone()),
+            move |res| Message::GotConnection(profile, res),
+        )
+    }
+
+    fn request_auth_code(&self) -> Command<Message> {
+        let profile = self.cfg.active_profile.clone();
+        Command::perform(get_auth_code(self.cfg.profile().com.clone()), move |res| {
+            Message::GotAuth(profile, res)
+        })
+    }
+
+    /// Drops the current connection and everything received over it
+    fn disconnect(&mut self) -> Command<Message> {
+        let mut commands = Vec::new();
+        if let Some(con) = self.connection.take() {
+            commands.push(Command::perform(con.close(), |res| {
+                if let Err(e) = res {
+                    warn!("Could not close connection: {:?}", e);
+                }
+                Message::None()
+            }));
+        }
+        self.player_state = None;
+        crash::set_connection(String::from("Not connected"));
+        crash::set_player_state(None);
+        commands.push(self.player_control.update(
+            &self.connection,
+            PlayerControlMessage::PlayerStateChanged(None),
+        ));
+        commands.push(
+            self.playlist_tab
+                .update(&self.connection, PlaylistMessage::PlayerStateChanged(None)),
+        );
+        commands.push(
+            self.history_tab
+                .update(&self.connection, HistoryMessage::PlayerStateChanged(None)),
+        );
+        commands.push(self.footer.update(FooterMessage::UpdateUser(None)));
+        commands.push(self.footer.update(FooterMessage::UpdateChannel(None)));
+        Command::batch(commands)
+    }
+
+    fn save_ui_state(&mut self) {
+        UiState {
+            window_size: self.window_size,
+            tab: TAB_KEYS[self.tabs.cur_tab()],
+            search_query: self.search_tab.query().to_string(),
+            search_results: self.search_tab.results(),
+            scroll: ScrollOffsets {
+                playlist: util::scroll_offset(self.playlist_tab.scroll_state()),
+                history: util::scroll_offset(self.history_tab.scroll_state()),
+                search: util::scroll_offset(self.search_tab.scroll_state()),
+                settings: util::scroll_offset(self.settings_tab.scroll_state()),
+            },
+        }
+        .save(&self.data_dir);
+    }
+
+    fn update_bundle(&mut self, message: BundleMessage) -> Command<Message> {
+        let mut commands = Vec::new();
+        let status = match message {
+            BundleMessage::Export() => {
+                let bundle = SettingsBundle::new(&self.cfg, self.library_tab.library());
+                return Command::perform(
+                    async move {
+                        let path = util::dialog::save_path(
+                            "Settings Bundle",
+                            &BUNDLE_EXTENSIONS,
+                            String::from("reciprocity_settings.yml"),
+                        )
+                        .await;
+                        match path {
+                            Some(path) => bundle.save(path).await.map(Some),
+                            None => Ok(None),
+                        }
+                    },
+                    |res| Message::Bundle(BundleMessage::Exported(res)),
+                );
+            }
+            BundleMessage::Exported(Ok(Some(path))) => format!("Exported to {:?}", path),
+            BundleMessage::Exported(Ok(None)) => return Command::none(),
+            BundleMessage::Exported(Err(e)) => e,
+            BundleMessage::Import() => {
+                return Command::perform(
+                    async {
+                        match util::dialog::open_path("Settings Bundle", &BUNDLE_EXTENSIONS).await
+                        {
+                            Some(path) => SettingsBundle::load(path).await.map(Some),
+                            None => Ok(None),
+                        }
+                    },
+                    |res| Message::Bundle(BundleMessage::Loaded(res)),
+                );
+            }
+            BundleMessage::Loaded(Ok(Some(bundle))) => {
+                let preview = bundle.preview(&self.cfg, self.library_tab.library());
+                self.pending_import = Some(bundle);
+                return self
+                    .settings_tab
+                    .update(SettingsMessage::ImportPreview(Some(preview)));
+            }
+            BundleMessage::Loaded(Ok(None)) => return Command::none(),
+            BundleMessage::Loaded(Err(e)) => e,
+            BundleMessage::Apply(mode) => match self.pending_import.take() {
+                Some(bundle) => {
+                    let bot =
+                        |cfg: &Config| (cfg.active_profile.clone(), cfg.profile().bot_link.clone());
+                    let active = bot(&self.cfg);
+                    let mut library = self.library_tab.library().clone();
+                    bundle.apply(&mut self.cfg, &mut library, mode);
+                    commands.push(self.library_tab.set_library(library));
+                    if active.0 != self.cfg.active_profile {
+                        self.cfg_overrides.clear_profile();
+                    }
+                    self.save_config();
+                    commands.push(self.settings_tab.update(self.settings_config_message()));
+                    //The bot of the active profile may have changed or it is gone
+                    if active != bot(&self.cfg) {
+                        info!("Bundle changed profile={}", self.cfg.active_profile);
+                        self.no_auth = false;
+                        commands.push(self.disconnect());
+                        commands.push(self.connect());
+                    }
+                    match mode {
+                        ImportMode::Merge => String::from("Bundle merged"),
+                        ImportMode::Replace => String::from("Profiles and playlists replaced"),
+                    }
+                }
+                None => return Command::none(),
+            },
+            BundleMessage::Cancel() => {
+                self.pending_import = None;
+                String::from("Import cancelled")
+            }
+        };
+
+        commands.push(
+            self.settings_tab
+                .update(SettingsMessage::ImportPreview(None)),
+        );
+        commands.push(
+            self.settings_tab
+                .update(SettingsMessage::BundleStatus(Some(status))),
+        );
+        Command::batch(commands)
+    }
+
+    fn settings_config_message(&self) -> SettingsMessage {
+        SettingsMessage::ConfigChanged(Box::new(self.cfg.clone()))
+    }
+
+    /// Makes the settings which are not read on demand take effect
+    fn apply_runtime_settings(&mut self) -> Command<Message> {
+        crate::log::reconfigure(self.cfg.log_level, &self.cfg.logging);
+        set_double_click_interval(self.cfg.double_click_interval());
+        util::set_image_cache_size(self.cfg.thumbnail_cache_size);
+        self.shortcuts.set_bindings(&self.cfg.shortcuts);
+        self.player_control
+            .set_seek_steps(self.cfg.rewind_step(), self.cfg.forward_step());
+        self.search_tab.update(
+            &self.connection,
+            SearchMessage::BackendChanged(self.cfg.search_backend.clone()),
+        )
+    }
+
+    /// Asks where to save the diagnostic bundle and writes it there
+    fn create_diagnostics(&self) -> Command<Message> {
+        let session = match self.logs_tab.include_session() {
+            true => Some(SessionCapture::new(
+                crash::connection_state(),
+                self.player_state.as_ref(),
+            )),
+            false => None,
+        };
+        let diagnostics = Diagnostics::new(
+            &self.cfg,
+            self.data_dir.clone(),
+            self.window_size,
+            session,
+        );
+        Command::perform(
+            async move {
+                let path = util::dialog::save_path(
+                    "Zip Archive",
+                    &DIAGNOSTIC_EXTENSIONS,
+                    diagnostics::file_name(),
+                )
+                .await;
+                match path {
+                    Some(path) => diagnostics.save(path).await.map(Some),
+                    None => Ok(None),
+                }
+            },
+            |res| Message::Logs(LogsMessage::DiagnosticsCreated(res)),
+        )
+    }
+
+    fn run_shortcut(&mut self, action: Action, c: &mut Clipboard) -> Command<Message> {
+        let message = match action {
+            Action::PlayPause => PlayerControlMessage::ButtonPressed(ButtonEvent::PlayPause),
+            Action::Next => PlayerControlMessage::ButtonPressed(ButtonEvent::Next),
+            Action::Previous => PlayerControlMessage::ButtonPressed(ButtonEvent::Prev),
+            Action::Repeat => PlayerControlMessage::ButtonPressed(ButtonEvent::Repeat),
+            Action::SeekBack => {
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Seek(-SHORT_SEEK_SECS))
+            }
+            Action::SeekForward => {
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Seek(SHORT_SEEK_SECS))
+            }
+            Action::SeekBackLong => {
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Seek(-LONG_SEEK_SECS))
+            }
+            Action::SeekForwardLong => {
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Seek(LONG_SEEK_SECS))
+            }
+            Action::PlaylistTab => return self.update(Message::TabSelected(PLAYLIST_TAB), c),
+            Action::HistoryTab => return self.update(Message::TabSelected(HISTORY_TAB), c),
+            Action::SearchTab => return self.update(Message::TabSelected(SEARCH_TAB), c),
+            Action::LibraryTab => return self.update(Message::TabSelected(LIBRARY_TAB), c),
+            Action::ActivityTab => return self.update(Message::TabSelected(ACTIVITY_TAB), c),
+            Action::LogsTab => return self.update(Message::TabSelected(LOGS_TAB), c),
+            Action::SettingsTab => return self.update(Message::TabSelected(SETTINGS_TAB), c),
+            Action::FocusSearch => {
+                self.search_tab.focus_input();
+                return self.update(Message::TabSelected(SEARCH_TAB), c);
+            }
+            //Toggled by the shortcuts themselves
+            Action::CheatSheet => return Command::none(),
+        };
+        self.player_control.update(&self.connection, message)
+    }
+
+    fn recover(
+        &mut self,
+        message: RecoveryMessage,
+        clipboard: &mut Clipboard,
+    ) -> Command<Message> {
+        match message {
+            RecoveryMessage::ResetConfig => match startup::reset_config(&self.cfg_path) {
+                Ok(mut cfg) => {
+                    self.cfg_overrides.apply(&mut cfg);
+                    self.theme = cfg.theme;
+                    self.cfg = cfg;
+                    self.recovery.dismiss();
+                    self.connect()
+                }
+                Err(e) => {
+                    self.recovery.set_status(e.to_string());
+                    Command::none()
+                }
+            },
+            RecoveryMessage::OpenConfigFolder => {
+                if let Some(dir) = self.cfg_path.parent() {
+                    if let Err(e) = open::that(dir) {
+                        self.recovery
+                            .set_status(format!("Could not open {:?}: {}", dir, e));
+                    }
+                }
+                Command::none()
+            }
+            RecoveryMessage::OpenCrashReport(path) => {
+                if let Err(e) = open::that(&path) {
+                    self.recovery
+                        .set_status(format!("Could not open {:?}: {}", path, e));
+                }
+                Command::none()
+            }
+            RecoveryMessage::CopyCrashReport(path) => {
+                match std::fs::read_to_string(&path) {
+                    Ok(report) => {
+                        clipboard.write(report);
+                        self.recovery
+                            .set_status(String::from("Crash report copied to clipboard"));
+                    }
+                    Err(e) => self
+                        .recovery
+                        .set_status(format!("Could not read {:?}: {}", path, e)),
+                }
+                Command::none()
+            }
+            RecoveryMessage::Continue => {
+                //The defaults are already loaded, but keep the broken config for the user to fix
+                self.cfg_writable = !self.recovery.has_config_error();
+                self.recovery.dismiss();
+                self.connect()
+            }
+        }
+    }
+}
+
 impl Application for Companion {
     type Executor = executor::TokioExecutor;
     type Message = Message;
-    type Flags = (Config, PathBuf);
+    type Flags = Flags;
 
-    fn new((cfg, config_path): (Config, PathBuf)) -> (Self, Command<Self::Message>) {
-        println!(
-            "Config refresh token: {:?}",
-            cfg.refresh_token.as_ref().map(|t| t.secret().clone())
+    fn new(flags: Flags) -> (Self, Command<Self::Message>) {
+        let Flags {
+            config: cfg,
+            config_path,
+            data_dir,
+            overrides: cfg_overrides,
+            no_auth,
+            startup_errors,
+            crash_report,
+            ui_state,
+        } = flags;
+        debug!(
+            "Starting with profile={} bot_link={} refresh token stored={}",
+            cfg.active_profile,
+            cfg.profile().bot_link,
+            cfg.profile().refresh_token.is_some()
         );
-        let command = if let Some(token) = cfg.refresh_token.as_ref() {
-            println!("Performing Connect with Refresh Token");
-            Command::perform(
-                Connection::new(Auth::Token(token.clone()), cfg.bot_link.clone()),
-                Message::GotConnection,
-            )
-        } else {
-            println!("Getting New Auth Code: Main");
-            Command::perform(get_auth_code(cfg.com.clone()), Message::GotAuth)
+
+        let log_dir = data_dir.clone();
+        let library_dir = data_dir.clone();
+        let (library, library_error) = match Library::load(&data_dir) {
+            Ok(library) => (library, None),
+            Err(e) => (Library::default(), Some(e)),
+        };
+        let mut companion = Companion {
+            cfg: cfg.clone(),
+            cfg_path: config_path,
+            cfg_overrides,
+            cfg_writable: true,
+            data_dir,
+            no_auth,
+            window_size: ui_state.window_size,
+            launch_font_size: cfg.font_size,
+            should_exit: false,
+            theme: cfg.theme,
+            connection: None,
+            player_state: None,
+            player_control: PlayerControl::new(),
+            footer: PlayerFooter::new(),
+            recovery: RecoveryView::new(startup_errors, crash_report),
+            banners: Banners::new(),
+            toasts: Toasts::new(),
+            shortcuts: Shortcuts::new(),
+            drag: Drag::default(),
+            pending_import: None,
+            tabs: Tabs::new(
+                TAB_KEYS
+                    .iter()
+                    .position(|k| *k == ui_state.tab)
+                    .unwrap_or(PLAYLIST_TAB),
+                Message::TabSelected,
+            ),
+            playlist_tab: PlaylistTab::new(),
+            history_tab: HistoryTab::new(),
+            search_tab: SearchTab::new(),
+            library_tab: LibraryTab::new(library_dir, library),
+            activity_tab: ActivityTab::new(),
+            logs_tab: LogsTab::new(log_dir),
+            settings_tab: SettingsTab::new(),
         };
+        companion
+            .settings_tab
+            .update(companion.settings_config_message());
+        companion.apply_runtime_settings();
+        crash::set_config(&companion.cfg);
+        if let Some(e) = library_error {
+            companion.fail(Error::LoadLibrary(Library::path(&companion.data_dir), e));
+        }
+        util::set_scroll_offset(
+            companion.playlist_tab.scroll_state(),
+            ui_state.scroll.playlist,
+        );
+        util::set_scroll_offset(companion.history_tab.scroll_state(), ui_state.scroll.history);
+        util::set_scroll_offset(companion.search_tab.scroll_state(), ui_state.scroll.search);
+        util::set_scroll_offset(
+            companion.settings_tab.scroll_state(),
+            ui_state.scroll.settings,
+        );
+        let search_cmd = companion
+            .search_tab
+            .restore(ui_state.search_query, ui_state.search_results);
 
-        (
-            Companion {
-                cfg: cfg.clone(),
-                cfg_path: config_path,
-                theme: cfg.theme,
-                connection: None,
-                player_state: None,
-                app_log: Vec::default(),
-                control_log: Vec::default(),
-                player_control: PlayerControl::new(),
-                footer: PlayerFooter::new(),
-                tabs: Tabs::new(0, Message::TabSelected),
-                playlist_tab: PlaylistTab::new(),
-                history_tab: HistoryTab::new(),
-                search_tab: SearchTab::new(),
-                settings_tab: SettingsTab::new(),
-            },
-            command,
-        )
+        //Wait for the user to decide how to recover before connecting
+        let command = match companion.recovery.is_active() {
+            true => Command::none(),
+            false => companion.connect(),
+        };
+
+        (companion, Command::batch(vec![search_cmd, command]))
     }
 
     fn title(&self) -> String {
         "Reciprocity Companion".to_string()
     }
 
-    fn update(&mut self, message: Self::Message, _c: &mut Clipboard) -> Command<Self::Message> {
+    fn update(&mut self, message: Self::Message, c: &mut Clipboard) -> Command<Self::Message> {
         match message {
             Message::None() => Command::none(),
-            Message::GotAuth(res) => match res {
-                Ok(code) => Command::perform(
-                    Connection::new(Auth::Code(code), self.cfg.bot_link.clone()),
-                    Message::GotConnection,
-                ),
-                Err(e) => panic!("{:?}", e),
-            },
+            Message::GotAuth(profile, res) => {
+                if profile != self.cfg.active_profile {
+                    //Profile was switched in the meantime
+                    return Command::none();
+                }
+                match res {
+                    Ok(code) => self.connect_with(Auth::Code(code)),
+                    Err(e) => {
+                        crash::set_connection(String::from("Not connected"));
+                        self.fail(Error::Auth(e));
+                        Command::none()
+                    }
+                }
+            }
             Message::PlayerControl(message) => {
                 self.player_control.update(&self.connection, message)
             }
             Message::Footer(message) => self.footer.update(message),
             Message::Playlist(message) => self.playlist_tab.update(&self.connection, message),
+            Message::Drag(DragMessage::AutoScroll()) => {
+                let (delta, height) = (self.drag.scroll(), self.drag.list_height());
+                match self.tabs.cur_tab() {
+                    PLAYLIST_TAB => self.playlist_tab.scroll_by(delta, height),
+                    LIBRARY_TAB => self.library_tab.scroll_by(delta, height),
+                    _ => {}
+                }
+                Command::none()
+            }
+            Message::Drag(message) => {
+                let dropped = self.drag.update(message);
+                let (active, target) = (self.drag.is_active(), self.drag.target());
+                self.playlist_tab.set_drag(self.drag.item(), target);
+                self.library_tab.set_drag(active, target);
+                let (item, target) = match dropped {
+                    Some(dropped) => dropped,
+                    None => return Command::none(),
+                };
+                //The target is only updated while a list is shown, tracks of other lists
+                //are handed over by value as their index means nothing there
+                match (self.tabs.cur_tab(), item) {
+                    (PLAYLIST_TAB, DragItem::Saved(i)) => match self.library_tab.track(i) {
+                        Some(track) => self.playlist_tab.drop(&self.connection, track),
+                        None => Command::none(),
+                    },
+                    (PLAYLIST_TAB, DragItem::Track(track)) => {
+                        self.playlist_tab.drop(&self.connection, track)
+                    }
+                    (LIBRARY_TAB, DragItem::Queued(i)) => match self.playlist_tab.track(i) {
+                        Some(track) => self
+                            .library_tab
+                            .drop(DragItem::Track(track.clone()), target),
+                        None => Command::none(),
+                    },
+                    (LIBRARY_TAB, item) => self.library_tab.drop(item, target),
+                    _ => Command::none(),
+                }
+            }
             Message::History(message) => self.history_tab.update(&self.connection, message),
             Message::Search(message) => self.search_tab.update(&self.connection, message),
+            Message::Library(message) => self.library_tab.update(&self.connection, message),
+            Message::AddToPlaylist(track) => {
+                let pick = LibraryMessage::Pick(track, self.tabs.cur_tab());
+                let pick_cmd = self.library_tab.update(&self.connection, pick);
+                Command::batch(vec![
+                    pick_cmd,
+                    self.update(Message::TabSelected(LIBRARY_TAB), c),
+                ])
+            }
+            Message::Activity(message) => self.activity_tab.update(message),
+            Message::Logs(LogsMessage::CreateDiagnostics()) => self.create_diagnostics(),
+            Message::Logs(message) => self.logs_tab.update(c, message),
             Message::Settings(message) => self.settings_tab.update(message),
-            Message::TabSelected(selected) => self.tabs.update(selected),
-            Message::ReceiveBotMessage(res) => {
-                println!("{:?}", res);
+            Message::Recovery(message) => self.recover(message, c),
+            Message::Bundle(message) => self.update_bundle(message),
+            Message::Banner(message) => match self.banners.update(message) {
+                Some(retry) => self.update(retry, c),
+                None => Command::none(),
+            },
+            Message::ShowToast(toast) => self.toasts.push(toast),
+            Message::Toast(message) => match self.toasts.update(message) {
+                Some(action) => self.update(action, c),
+                None => Command::none(),
+            },
+            //The player is hidden while recovering
+            Message::Shortcut(_) if self.recovery.is_active() => Command::none(),
+            Message::Shortcut(message) => match self.shortcuts.update(message) {
+                Some(action) => self.run_shortcut(action, c),
+                None => Command::none(),
+            },
+            Message::Error(e) => {
+                self.fail(e);
+                Command::none()
+            }
+            Message::SendControl(req) => match self.connection.as_ref() {
+                Some(con) => con.control_request(req),
+                None => {
+                    self.fail(Error::Request(req, ConnectionError::Closed));
+                    Command::none()
+                }
+            },
+            Message::SaveConfig() => {
+                self.save_config();
+                Command::none()
+            }
+            Message::Ping() => match self.connection.as_ref() {
+                Some(con) => con.get_ping_cmd(),
+                None => Command::none(),
+            },
+            Message::SettingChanged(setting) => {
+                self.cfg_overrides.clear_setting(&setting);
+                self.cfg.apply(setting);
+                self.save_config();
+                self.apply_runtime_settings()
+            }
+            Message::Reconnect() => {
+                self.no_auth = false;
+                let disconnect_cmd = self.disconnect();
+                Command::batch(vec![disconnect_cmd, self.connect()])
+            }
+            Message::TabSelected(selected) => {
+                if selected == LOGS_TAB {
+                    self.logs_tab.update(c, LogsMessage::Refresh());
+                }
+                self.tabs.update(selected)
+            }
+            Message::WindowResized(width, height) => {
+                self.window_size = (width, height);
+                Command::none()
+            }
+            Message::CloseRequested() => {
+                self.save_ui_state();
+                self.should_exit = true;
+                Command::none()
+            }
+            Message::ReceiveBotMessage(id, res) => {
+                if self.connection.as_ref().map(|c| c.id()) != Some(id) {
+                    //Leftover of a closed connection
+                    return Command::none();
+                }
+                trace!("Received connection={} message={:?}", id, res);
                 let msg = match res {
                     Ok(msg) => msg,
                     Err(e) => {
-                        panic!("{:?}", e);
+                        //The banner offers to reconnect
+                        self.fail(Error::ConnectionLost(e));
+                        return self.disconnect();
                     }
                 };
                 let mut commands = Vec::new();
                 match &msg {
                     ComMessage::PlayerState(state) => {
+                        let old_track = self
+                            .player_state
+                            .as_ref()
+                            .and_then(|s| s.current.as_ref())
+                            .map(|t| t.uri.clone());
                         if let Some(state) = state {
                             match state {
                                 State::FullState(full) => {
                                     self.player_state = Some(full.deref().clone())
                                 }
                                 State::UpdateState(patch) => {
-                                    println!("Patch Length: {} Bytes", patch.len());
-                                    msg.patch_player_state(
-                                        &mut self.player_state.as_mut().unwrap(),
-                                    )
-                                    .ok();
+                                    trace!("Patching player state, patch_len={}", patch.len());
+                                    match self.player_state.as_mut() {
+                                        Some(state) => {
+                                            if let Err(e) = msg.patch_player_state(state) {
+                                                warn!("Could not patch player state: {:?}", e);
+                                            }
+                                        }
+                                        None => warn!("Got state patch without a player state"),
+                                    }
                                 }
                                 State::EmptyState() => {
                                     self.player_state = None;
@@ -169,6 +812,14 @@ impl Application for Companion {
                         } else {
                             self.player_state = None;
                         }
+                        crash::set_player_state(self.player_state.as_ref());
+                        let new_track = self.player_state.as_ref().and_then(|s| s.current.as_ref());
+                        let changed = new_track.filter(|t| Some(&t.uri) != old_track.as_ref());
+                        if let Some(track) = changed {
+                            let playing = Toast::info(format!("Now playing: {}", track.title))
+                                .on_click(Message::TabSelected(PLAYLIST_TAB));
+                            commands.push(self.toasts.push(playing));
+                        }
 
                         commands.push(self.player_control.update(
                             &self.connection,
@@ -186,50 +837,87 @@ impl Application for Companion {
                     }
                     ComMessage::UserVoiceState(voice) => {
                         //TODO Send to all who are interested
+                        let channel = voice.as_ref().map(|v| v.channel_name.clone());
+                        if channel.as_deref() != self.footer.voice_channel() {
+                            let text = match channel.as_ref() {
+                                Some(channel) => format!("Joined {}", channel),
+                                None => String::from("Left the voice channel"),
+                            };
+                            commands.push(self.toasts.push(Toast::info(text)));
+                        }
 
-                        commands.push(self.footer.update(FooterMessage::UpdateChannel(
-                            voice.as_ref().map(|v| v.channel_name.clone()),
-                        )))
+                        commands.push(self.footer.update(FooterMessage::UpdateChannel(channel)))
+                    }
+                    ComMessage::PlayerControlResult(res) => {
+                        let skip = matches!(
+                            res.control,
+                            ControlRequest::Skip(_) | ControlRequest::BackSkip(_)
+                        );
+                        let own = Some(res.user.username.as_str()) == self.footer.user();
+                        if skip && !own && res.result.is_ok() {
+                            let skipped = Toast::info(format!("{} skipped", res.user.username))
+                                .on_click(Message::TabSelected(ACTIVITY_TAB));
+                            commands.push(self.toasts.push(skipped));
+                        }
+                        commands.push(
+                            self.activity_tab
+                                .update(ActivityMessage::Reported(res.clone())),
+                        );
                     }
                     _ => {}
                 }
-                commands.push(
-                    self.connection
-                        .as_ref()
-                        .expect("Connection is empty")
-                        .get_rec_cmd(),
-                );
+                if let Some(con) = self.connection.as_ref() {
+                    commands.push(con.get_rec_cmd());
+                }
                 Command::batch(commands)
             }
-            Message::GotConnection(res) => {
+            Message::GotConnection(profile, res) => {
+                if profile != self.cfg.active_profile {
+                    //Profile was switched in the meantime
+                    if let Ok((con, _)) = res {
+                        return Command::perform(con.close(), |_| Message::None());
+                    }
+                    return Command::none();
+                }
                 match res {
                     Ok((con, (user, token))) => {
-                        self.connection = Some(con);
-                        self.cfg.refresh_token = Some(token);
-                        self.cfg.update(self.cfg_path.clone());
+                        crash::set_connection(format!(
+                            "Connected to {} as {} with profile {}",
+                            self.cfg.profile().bot_link,
+                            user.username,
+                            profile
+                        ));
+                        self.cfg.profile_mut().refresh_token = Some(token);
+                        self.save_config();
+                        let connected = Toast::success(format!("Connected as {}", user.username));
+                        let toast_cmd = self.toasts.push(connected);
                         let footer_cmd = self
                             .footer
                             .update(FooterMessage::UpdateUser(Some(user.username)));
                         //Start Receive Chain
-                        let rec_cmd = self.connection.as_ref().unwrap().get_rec_cmd();
-                        Command::batch(vec![footer_cmd, rec_cmd])
+                        let rec_cmd = con.get_rec_cmd();
+                        let ping_cmd = con.get_ping_cmd();
+                        self.connection = Some(con);
+                        Command::batch(vec![footer_cmd, toast_cmd, rec_cmd, ping_cmd])
                     }
                     Err(e) => {
                         match e {
                             ConnectionError::NonAuthMessage(e) => {
-                                println!("Auth Error {:?}", e);
+                                warn!("Authentication failed: {:?}", e);
                                 //Clear Token in Config
-                                self.cfg.refresh_token = None;
-                                self.cfg.update(self.cfg_path.clone());
+                                self.cfg.profile_mut().refresh_token = None;
+                                self.save_config();
                                 //Attempt getting new Token
-                                println!("Getting New Auth Code: Got Connection");
-                                Command::perform(
-                                    get_auth_code(self.cfg.com.clone()),
-                                    Message::GotAuth,
-                                )
+                                debug!(
+                                    "Requesting auth code, profile={}",
+                                    self.cfg.active_profile
+                                );
+                                self.request_auth_code()
                             }
                             _ => {
-                                panic!("Error connecting: {:?}", e);
+                                crash::set_connection(String::from("Not connected"));
+                                self.fail(Error::Connect(e));
+                                Command::none()
                             }
                         }
                     }
@@ -238,11 +926,117 @@ impl Application for Companion {
             Message::ThemeChanged(theme) => {
                 self.theme = theme;
                 self.cfg.theme = theme;
-                self.cfg.update(self.cfg_path.clone());
+                self.cfg_overrides.clear_theme();
+                self.save_config();
 
                 Command::none()
             }
+            Message::ProfileSelected(profile) => {
+                if profile == self.cfg.active_profile || !self.cfg.profiles.contains_key(&profile)
+                {
+                    return Command::none();
+                }
+                info!("Switching profile={}", profile);
+                self.cfg.active_profile = profile;
+                self.cfg_overrides.clear_profile();
+                self.no_auth = false;
+                self.save_config();
+                self.settings_tab.update(self.settings_config_message());
+
+                let disconnect_cmd = self.disconnect();
+                Command::batch(vec![disconnect_cmd, self.connect()])
+            }
+            Message::ProfileEdited(edit) => {
+                let active_profile = self.cfg.active_profile.clone();
+                if let Err(e) = self.cfg.edit_profile(edit.clone()) {
+                    return self.toasts.push(Toast::warning(e));
+                }
+                info!("Edited profiles, {:?}", edit);
+                if let ProfileEdit::Rename(old, new) = &edit {
+                    self.cfg_overrides.rename_profile(old, new);
+                }
+                self.save_config();
+                self.settings_tab.update(self.settings_config_message());
+                if matches!(edit, ProfileEdit::Delete(name) if name == active_profile) {
+                    //The active profile is gone, continue with the one normalize picked
+                    self.cfg_overrides.clear_profile();
+                    self.no_auth = false;
+                    let disconnect_cmd = self.disconnect();
+                    return Command::batch(vec![disconnect_cmd, self.connect()]);
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let window_events = iced_native::subscription::events_with(|event, status| match event {
+            iced_native::Event::Window(iced_native::window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            iced_native::Event::Window(iced_native::window::Event::CloseRequested) => {
+                Some(Message::CloseRequested())
+            }
+            //Keys captured by a focused text input are typed, not shortcuts
+            iced_native::Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+                key_code,
+                modifiers,
+            }) if status == iced_native::event::Status::Ignored => Some(Message::Shortcut(
+                ShortcutMessage::KeyPressed(key_code, modifiers),
+            )),
+            _ => None,
+        });
+        let mut subscriptions = vec![window_events];
+        //The cursor leaves the pressed row while dragging, so it is followed in the whole window
+        if self.drag.is_pressed() {
+            subscriptions.push(iced_native::subscription::events_with(
+                |event, _| match event {
+                    iced_native::Event::Mouse(iced_native::mouse::Event::CursorMoved {
+                        position,
+                    }) => Some(Message::Drag(DragMessage::Moved(position))),
+                    iced_native::Event::Mouse(iced_native::mouse::Event::ButtonReleased(
+                        iced_native::mouse::Button::Left,
+                    )) => Some(Message::Drag(DragMessage::Released())),
+                    _ => None,
+                },
+            ));
+        }
+        if self.drag.scroll() != 0.0 {
+            subscriptions.push(
+                iced::time::every(SCROLL_INTERVAL)
+                    .map(|_| Message::Drag(DragMessage::AutoScroll())),
+            );
+        }
+        if self.player_control.is_playing() || self.player_control.is_sleep_timer_running() {
+            subscriptions.push(
+                iced::time::every(TICK_INTERVAL)
+                    .map(|_| Message::PlayerControl(PlayerControlMessage::Tick())),
+            );
+        }
+        if self.connection.is_some() {
+            subscriptions.push(iced::time::every(PING_INTERVAL).map(|_| Message::Ping()));
         }
+        if self.library_tab.is_enqueuing() {
+            subscriptions.push(
+                iced::time::every(ENQUEUE_INTERVAL)
+                    .map(|_| Message::Library(LibraryMessage::EnqueueNext())),
+            );
+        }
+        if self.tabs.cur_tab() == LOGS_TAB {
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(1))
+                    .map(|_| Message::Logs(LogsMessage::Refresh())),
+            );
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    fn should_exit(&self) -> bool {
+        self.should_exit
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.cfg.font_size as f64 / self.launch_font_size as f64
     }
 
     fn view(&mut self) -> Element<'_, Self::Message> {
@@ -250,20 +1044,35 @@ impl Application for Companion {
         //Column::new();
         //TODO
 
+        if self.recovery.is_active() {
+            return self.recovery.view(&self.theme);
+        }
+
         let (tabs, tab_view) = self.tabs.view(
             [
                 self.playlist_tab.borrowed(),
                 self.history_tab.borrowed(),
                 self.search_tab.borrowed(),
+                self.library_tab.borrowed(),
+                self.activity_tab.borrowed(),
+                self.logs_tab.borrowed(),
                 self.settings_tab.borrowed(),
             ],
             &self.theme,
+            self.drag.is_active(),
         );
+        let tab_view = match self.shortcuts.is_cheat_sheet_shown() {
+            true => self.shortcuts.view(&self.theme),
+            false => tab_view,
+        };
         let (song_picture, control) = self.player_control.view(&self.theme);
         let control = Container::new(control).style(self.theme.control_container_theme());
 
-        let tabs_song_column = Column::new()
-            .push(tabs)
+        let mut tabs_song_column = Column::new().push(tabs);
+        if !self.toasts.is_empty() {
+            tabs_song_column = tabs_song_column.push(self.toasts.view(&self.theme));
+        }
+        let tabs_song_column = tabs_song_column
             .push(song_picture)
             .height(Length::Fill)
             .width(Length::Units(200));
@@ -282,7 +1091,11 @@ impl Application for Companion {
             .width(Length::Fill)
             .height(Length::Fill);
 
-        let col = Column::new()
+        let mut col = Column::new();
+        if !self.banners.is_empty() {
+            col = col.push(self.banners.view(&self.theme));
+        }
+        let col = col
             .push(combined_row)
             //.push(Rule::horizontal(1))
             .push(control)
@@ -0,0 +1,110 @@
+use reciprocity_communication::messages::Track;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const LIBRARY_FILE: &str = "playlists.yml";
+/// Extension a broken library is moved to, so it is not overwritten
+const BACKUP_EXTENSION: &str = "yml.bak";
+/// Extension the library is written to before it replaces the old file
+const TEMP_EXTENSION: &str = "yml.tmp";
+
+/// Track of a saved playlist, without the playback position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedTrack {
+    pub uri: String,
+    pub title: String,
+    #[serde(default)]
+    pub len: Duration,
+}
+
+impl SavedTrack {
+    pub fn track(&self) -> Track {
+        Track {
+            len: self.len,
+            pos: Default::default(),
+            title: self.title.clone(),
+            uri: self.uri.clone(),
+        }
+    }
+}
+
+impl From<&Track> for SavedTrack {
+    fn from(track: &Track) -> Self {
+        SavedTrack {
+            uri: track.uri.clone(),
+            title: track.title.clone(),
+            len: track.len,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlaylist {
+    pub name: String,
+    #[serde(default)]
+    pub tracks: Vec<SavedTrack>,
+}
+
+impl SavedPlaylist {
+    pub fn len(&self) -> Duration {
+        self.tracks.iter().map(|t| t.len).sum()
+    }
+}
+
+/// Playlists saved locally in the data dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Library {
+    #[serde(default)]
+    pub playlists: Vec<SavedPlaylist>,
+}
+
+impl Library {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(LIBRARY_FILE)
+    }
+
+    /// Loads the saved playlists. A broken file is moved aside before the error is returned
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        let path = Library::path(data_dir);
+        if !path.exists() {
+            return Ok(Library::default());
+        }
+        let file = File::open(&path).map_err(|e| e.to_string())?;
+        serde_yaml::from_reader(file).map_err(|e| {
+            let backup = path.with_extension(BACKUP_EXTENSION);
+            match std::fs::rename(&path, &backup) {
+                Ok(()) => format!("{}, moved to {:?}", e, backup),
+                Err(_) => e.to_string(),
+            }
+        })
+    }
+
+    /// Writes to a temporary file first, so a crash while writing keeps the saved playlists
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        let bytes = serde_yaml::to_vec(self).map_err(|e| e.to_string())?;
+        let path = Library::path(data_dir);
+        let temp = path.with_extension(TEMP_EXTENSION);
+        File::create(&temp)
+            .and_then(|mut f| {
+                f.write_all(bytes.as_slice())?;
+                f.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temp, &path))
+            .map_err(|e| e.to_string())
+    }
+
+    /// The name, numbered if a playlist already has it
+    pub fn unique_name(&self, name: &str) -> String {
+        let taken = |name: &str| self.playlists.iter().any(|p| p.name == name);
+        let mut unique = name.to_string();
+        let mut i = 2;
+        while taken(&unique) {
+            unique = format!("{} ({})", name, i);
+            i += 1;
+        }
+        unique
+    }
+}
@@ -1,4 +1,52 @@
-use log::{warn, error, debug, info};
+use crate::config::LogSettings;
+use crate::startup::StartupError;
+use chrono::{DateTime, Local, NaiveDateTime};
+use log::{debug, error, info, warn, Level, LevelFilter, Record};
+use log4rs::append::rolling_file::policy::compound::roll::delete::DeleteRoller;
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::roll::Roll;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use log4rs::append::rolling_file::policy::compound::trigger::Trigger;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::{LogFile, RollingFileAppender};
+use log4rs::append::Append;
+use log4rs::config::{Appender, Logger, Root};
+use log4rs::encode::json::JsonEncoder;
+use log4rs::encode::pattern::PatternEncoder;
+use log4rs::encode::{Encode, Write};
+use log4rs::Handle;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+use std::time::{Duration, SystemTime};
+
+/// Number of records kept for the log viewer
+pub const LOG_BUFFER_SIZE: usize = 2000;
+/// Name of the active log file, rotated files get a number appended
+pub const LOG_FILE_NAME: &str = "companion";
+const LOG_FILE_EXTENSION: &str = "log";
+/// Name of the per-start log files written by old versions, without the extension
+const OLD_LOG_FILE_FORMAT: &str = "%Y-%m-%d %H_%M_%S";
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+/// Keys whose values never end up in a log, matched case-insensitively as part of a name
+const SECRET_KEYS: [&str; 6] = [
+    "token",
+    "secret",
+    "password",
+    "auth_code",
+    "authorization_code",
+    "authorizationcode",
+];
+/// Keys too common in other text, only matched as parameter of a query string like `?code=`
+const QUERY_SECRET_KEYS: [&str; 1] = ["code"];
+const REDACTED: &str = "<redacted>";
+
+/// The running logger, rebuilt at runtime when its settings change
+static LOGGER: Lazy<Mutex<Option<Logger>>> = Lazy::new(|| Mutex::new(None));
+
+static LOG_BUFFER: Lazy<Mutex<VecDeque<LogRecord>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(LOG_BUFFER_SIZE)));
 
 #[derive(Clone, Debug)]
 pub enum LogMessage{
@@ -17,4 +65,491 @@ impl LogMessage{
             LogMessage::Info(m) => info!("{}", m),
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.time.format("%H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Handle of the logger with the settings it was built from
+#[derive(Debug)]
+struct Logger {
+    handle: Handle,
+    log_dir: PathBuf,
+    level: LevelFilter,
+    settings: LogSettings,
+}
+
+/// Keeps the latest records in memory for the log viewer
+#[derive(Debug)]
+pub struct BufferAppender;
+
+impl Append for BufferAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let record = LogRecord {
+            time: Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: redact(&record.args().to_string()),
+        };
+        //While panicking the buffer may be held by the panicking code, so it is never waited for
+        let mut buffer = match LOG_BUFFER.try_lock() {
+            Ok(buffer) => buffer,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) if std::thread::panicking() => return Ok(()),
+            Err(TryLockError::WouldBlock) => {
+                LOG_BUFFER.lock().unwrap_or_else(PoisonError::into_inner)
+            }
+        };
+        if buffer.len() >= LOG_BUFFER_SIZE {
+            buffer.pop_front();
+        }
+        buffer.push_back(record);
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+/// Copy of all buffered records, oldest first
+pub fn buffered_records() -> Vec<LogRecord> {
+    LOG_BUFFER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// The latest buffered records, oldest first.
+/// Does not wait for the buffer, as it is used while panicking.
+pub fn recent_records(count: usize) -> Vec<LogRecord> {
+    match LOG_BUFFER.try_lock() {
+        Ok(buffer) => {
+            let skip = buffer.len().saturating_sub(count);
+            buffer.iter().skip(skip).cloned().collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Replaces values following secret keys like `refresh_token=...`, `secret: "..."` or `Token(...)`
+pub fn redact(message: &str) -> String {
+    let lower = message.to_ascii_lowercase();
+    let bytes = message.as_bytes();
+    let mut out = String::with_capacity(message.len());
+    let mut copied = 0;
+    let mut pos = 0;
+    while let Some((key_end, value_start)) = next_secret(&lower, pos) {
+        //Skip quotes and a `Some(` wrapper around the value
+        let mut start = value_start;
+        if lower[start..].starts_with("some(") {
+            start += "some(".len();
+        }
+        let quoted = bytes.get(start) == Some(&b'"');
+        if quoted {
+            start += 1;
+        }
+        let end = match quoted {
+            true => quote_end(message, start),
+            false => value_end(message, start),
+        };
+        if end > start {
+            out.push_str(&message[copied..start]);
+            out.push_str(REDACTED);
+            copied = end;
+        }
+        pos = end.max(key_end);
+    }
+    out.push_str(&message[copied..]);
+    out
+}
+
+/// Index of the quote closing the string starting at `start`, or the end of the message
+fn quote_end(message: &str, start: usize) -> usize {
+    let mut escaped = false;
+    for (i, c) in message[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return start + i,
+            _ => {}
+        }
+    }
+    message.len()
+}
+
+/// End of an unquoted value, which includes everything up to the matching bracket,
+/// like the nested string of `AuthorizationCode("...")`
+fn value_end(message: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while let Some(c) = message[i..].chars().next() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => return i,
+            ')' | ']' | '}' => depth -= 1,
+            //Nested strings may contain brackets and separators
+            '"' => {
+                i = quote_end(message, i + 1);
+                if i == message.len() {
+                    return i;
+                }
+            }
+            ',' | '&' | ';' if depth == 0 => return i,
+            _ if depth == 0 && c.is_whitespace() => return i,
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    message.len()
+}
+
+/// Finds the next secret key followed by a separator, returning where the key and separator end
+fn next_secret(lower: &str, from: usize) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for key in SECRET_KEYS.iter() {
+        let mut search = from;
+        while let Some(i) = lower[search..].find(key) {
+            let key_end = search + i + key.len();
+            //Allow the end of identifiers like `refresh_token_secret` and quoted keys
+            let rest =
+                lower[key_end..].trim_start_matches(|c: char| c.is_alphanumeric() || c == '_');
+            let rest_start = lower.len() - rest.len();
+            let sep = rest.trim_start_matches(|c: char| c == '"' || c.is_whitespace());
+            let sep_start = lower.len() - sep.len();
+            let value = match sep.chars().next() {
+                Some('=') | Some(':') | Some('(') => sep_start + 1,
+                _ => {
+                    search = key_end;
+                    continue;
+                }
+            };
+            let value = value + (lower[value..].len() - lower[value..].trim_start().len());
+            if best.map(|(k, _)| rest_start < k).unwrap_or(true) {
+                best = Some((rest_start, value));
+            }
+            break;
+        }
+    }
+    for key in QUERY_SECRET_KEYS.iter() {
+        let mut search = from;
+        while let Some(i) = lower[search..].find(key) {
+            let key_start = search + i;
+            let key_end = key_start + key.len();
+            search = key_end;
+            let parameter = lower[..key_start].ends_with(['?', '&']);
+            if !parameter || !lower[key_end..].starts_with('=') {
+                continue;
+            }
+            if best.map(|(k, _)| key_end < k).unwrap_or(true) {
+                best = Some((key_end, key_end + 1));
+            }
+            break;
+        }
+    }
+    best
+}
+
+/// Redacts secrets before handing the record to the wrapped encoder
+#[derive(Debug)]
+struct RedactingEncoder(Box<dyn Encode>);
+
+impl Encode for RedactingEncoder {
+    fn encode(&self, w: &mut dyn Write, record: &Record) -> anyhow::Result<()> {
+        let message = redact(&record.args().to_string());
+        self.0.encode(
+            w,
+            &Record::builder()
+                .args(format_args!("{}", message))
+                .level(record.level())
+                .target(record.target())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        )
+    }
+}
+
+/// Rotates the log file once it got older than the given age
+#[derive(Debug)]
+struct AgeTrigger {
+    max_age: Duration,
+    created: Mutex<SystemTime>,
+}
+
+impl AgeTrigger {
+    fn new(path: &Path, max_age: Duration) -> Self {
+        //Files continued from a previous start keep their age
+        let created = std::fs::metadata(path)
+            .and_then(|m| m.created().or_else(|_| m.modified()))
+            .unwrap_or_else(|_| SystemTime::now());
+        AgeTrigger {
+            max_age,
+            created: Mutex::new(created),
+        }
+    }
+}
+
+impl Trigger for AgeTrigger {
+    fn trigger(&self, file: &LogFile) -> anyhow::Result<bool> {
+        let mut created = self.created.lock().unwrap();
+        let expired =
+            file.len_estimate() > 0 && created.elapsed().map(|a| a > self.max_age).unwrap_or(false);
+        if expired {
+            *created = SystemTime::now();
+        }
+        Ok(expired)
+    }
+}
+
+/// Rotates as soon as any of the triggers fires
+#[derive(Debug)]
+struct AnyTrigger(Vec<Box<dyn Trigger>>);
+
+impl Trigger for AnyTrigger {
+    fn trigger(&self, file: &LogFile) -> anyhow::Result<bool> {
+        for trigger in self.0.iter() {
+            if trigger.trigger(file)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn log_file_path(log_dir: &Path) -> PathBuf {
+    log_dir.join(format!("{}.{}", LOG_FILE_NAME, LOG_FILE_EXTENSION))
+}
+
+fn log_config(
+    log_dir: &Path,
+    level: LevelFilter,
+    settings: &LogSettings,
+) -> Result<log4rs::config::Config, StartupError> {
+    let log_path = log_file_path(log_dir);
+
+    let mut triggers: Vec<Box<dyn Trigger>> = Vec::new();
+    if settings.max_file_size_mb > 0 {
+        triggers.push(Box::new(SizeTrigger::new(
+            settings.max_file_size_mb * 1024 * 1024,
+        )));
+    }
+    if settings.max_file_age_days > 0 {
+        triggers.push(Box::new(AgeTrigger::new(
+            &log_path,
+            Duration::from_secs(settings.max_file_age_days * SECS_PER_DAY),
+        )));
+    }
+    let roller: Box<dyn Roll> = match settings.max_files {
+        0 => Box::new(DeleteRoller::new()),
+        count => {
+            let pattern = log_dir.join(format!("{}.{{}}.{}", LOG_FILE_NAME, LOG_FILE_EXTENSION));
+            Box::new(
+                FixedWindowRoller::builder()
+                    .base(1)
+                    .build(&pattern.to_string_lossy(), count)
+                    .map_err(|e| StartupError::LogConfig(e.to_string()))?,
+            )
+        }
+    };
+    let policy = CompoundPolicy::new(Box::new(AnyTrigger(triggers)), roller);
+
+    let encoder: Box<dyn Encode> = match settings.json {
+        true => Box::new(JsonEncoder::new()),
+        false => Box::new(PatternEncoder::new(
+            "{d(%Y-%m-%d %H:%M:%S%.3f)} {l:<5} {t} - {m}{n}",
+        )),
+    };
+    let logfile = RollingFileAppender::builder()
+        .encoder(Box::new(RedactingEncoder(encoder)))
+        .build(log_path.clone(), Box::new(policy))
+        .map_err(|e| StartupError::CreateLogFile(log_path, Arc::new(e)))?;
+
+    let mut config = log4rs::config::Config::builder()
+        .appender(Appender::builder().build("logfile", Box::new(logfile)))
+        .appender(Appender::builder().build("buffer", Box::new(BufferAppender)));
+    for (module, module_level) in settings.modules.iter() {
+        config = config.logger(Logger::builder().build(module, *module_level));
+    }
+    config
+        .build(
+            Root::builder()
+                .appender("logfile")
+                .appender("buffer")
+                .build(level),
+        )
+        .map_err(|e| StartupError::LogConfig(e.to_string()))
+}
+
+/// Sets up the rotating log file in the directory and the buffer for the log viewer
+pub fn init(
+    log_dir: &Path,
+    level: LevelFilter,
+    settings: &LogSettings,
+) -> Result<(), StartupError> {
+    remove_old_logs(log_dir, settings);
+    let config = log_config(log_dir, level, settings)?;
+    let handle = log4rs::init_config(config).map_err(|e| StartupError::InitLog(e.to_string()))?;
+    *LOGGER.lock().unwrap() = Some(Logger {
+        handle,
+        log_dir: log_dir.to_path_buf(),
+        level,
+        settings: settings.clone(),
+    });
+    Ok(())
+}
+
+/// Rebuilds the logger if the root level or the logging settings changed
+pub fn reconfigure(level: LevelFilter, settings: &LogSettings) {
+    let mut guard = LOGGER.lock().unwrap();
+    let logger = match guard.as_mut() {
+        Some(logger) if logger.level != level || &logger.settings != settings => logger,
+        _ => return,
+    };
+    match log_config(&logger.log_dir, level, settings) {
+        Ok(config) => {
+            logger.handle.set_config(config);
+            logger.level = level;
+            logger.settings = settings.clone();
+        }
+        Err(e) => error!("Could not change the log settings: {}", e),
+    }
+}
+
+/// Log files in the directory including rotated ones and those of old versions, newest first
+pub fn log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<(SystemTime, PathBuf)> = match std::fs::read_dir(log_dir) {
+        Ok(dir) => dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| is_companion_log(p))
+            .filter_map(|p| {
+                let modified = std::fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+                Some((modified, p))
+            })
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, p)| p).collect()
+}
+
+/// Whether the file is the active or a rotated log file, or one of an old version.
+/// Other logs in a shared data dir are left alone.
+fn is_companion_log(path: &Path) -> bool {
+    let extension = format!(".{}", LOG_FILE_EXTENSION);
+    let stem = match path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_suffix(extension.as_str()))
+    {
+        Some(stem) => stem,
+        None => return false,
+    };
+    let rotated = stem
+        .strip_prefix(LOG_FILE_NAME)
+        .and_then(|s| s.strip_prefix('.'))
+        .map(|n| n.parse::<u32>().is_ok())
+        .unwrap_or(false);
+    stem == LOG_FILE_NAME
+        || rotated
+        || NaiveDateTime::parse_from_str(stem, OLD_LOG_FILE_FORMAT).is_ok()
+}
+
+/// Deletes log files beyond the retention limit, including the per-start files of old versions
+fn remove_old_logs(log_dir: &Path, settings: &LogSettings) {
+    let active = log_file_path(log_dir);
+    let old = log_files(log_dir).into_iter().filter(|p| p != &active);
+    for path in old.skip(settings.max_files as usize) {
+        //The logger is not running yet, so failures can not be logged
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+
+    #[test]
+    fn redacts_assignments() {
+        assert_eq!(
+            redact("refresh_token=abc def"),
+            "refresh_token=<redacted> def"
+        );
+        assert_eq!(
+            redact("client_secret: \"a b\", x"),
+            "client_secret: \"<redacted>\", x"
+        );
+        assert_eq!(redact("PASSWORD = hunter2"), "PASSWORD = <redacted>");
+        assert_eq!(redact("auth_code=xyz;"), "auth_code=<redacted>;");
+    }
+
+    #[test]
+    fn redacts_nested_values() {
+        assert_eq!(
+            redact("Code(AuthorizationCode(\"x\"))"),
+            "Code(AuthorizationCode(\"<redacted>\"))"
+        );
+        assert_eq!(
+            redact("refresh_token: Some(RefreshToken(\"a(b\")), user: 1"),
+            "refresh_token: Some(<redacted>), user: 1"
+        );
+        assert_eq!(
+            redact("Token(\n    \"abc\",\n)"),
+            "Token(\n    \"<redacted>\",\n)"
+        );
+    }
+
+    #[test]
+    fn redacts_query_parameters() {
+        assert_eq!(
+            redact("GET /callback?code=abc&state=1"),
+            "GET /callback?code=<redacted>&state=1"
+        );
+        assert_eq!(
+            redact("/auth?state=1&code=abc"),
+            "/auth?state=1&code=<redacted>"
+        );
+    }
+
+    #[test]
+    fn keeps_harmless_text() {
+        let messages = [
+            "status code: 404",
+            "error code=5",
+            "Enqueued 3 tracks",
+            "token",
+            "the secret is out",
+        ];
+        for message in messages.iter() {
+            assert_eq!(&redact(message), message);
+        }
+    }
+
+    #[test]
+    fn escaped_quotes_stay_redacted() {
+        assert_eq!(
+            redact("secret=\"a\\\"b\" rest"),
+            "secret=\"<redacted>\" rest"
+        );
+    }
+}
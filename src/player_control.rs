@@ -1,13 +1,32 @@
+use crate::clock::PlaybackClock;
+use crate::config::DEFAULT_SEEK_STEP;
 use crate::connection::Connection;
 use crate::icons::Icon;
 use crate::theme::Theme;
-use crate::util::{duration_fmt, CompTrack};
+use crate::toast::{self, Toast};
+use crate::util::hover::HoverArea;
+use crate::util::markers::Markers;
+use crate::util::storyboard::Storyboard;
+use crate::util::wheel::WheelArea;
+use crate::util::{duration_fmt, duration_parse, get_yt_identifier, CompTrack};
 use crate::Message;
-use iced::{Align, Button, Command, Container, Element, Image, Length, Row, Slider, Space, Text};
+use iced::{
+    Align, Button, Column, Command, Container, Element, Image, Length, PickList, Row, Slider,
+    Space, Text, TextInput,
+};
+use log::{debug, warn};
 use reciprocity_communication::messages::PlayerControl as ControlRequest;
 use reciprocity_communication::messages::{PlayMode, PlayerState};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
 use std::time::{Duration, Instant};
 
+/// Minutes a custom sleep timer may run, up to a day
+const SLEEP_MINUTES: RangeInclusive<u64> = 1..=24 * 60;
+/// How long before the end of the track "End of track" pauses, on top of the latency,
+/// so the pause arrives before the bot starts the next track or loops this one
+const END_OF_TRACK_LEAD: Duration = Duration::from_millis(300);
+
 #[derive(Debug, Clone)]
 pub enum PlayerControlMessage {
     SongImageUpdated(Option<CompTrack>),
@@ -15,7 +34,20 @@ pub enum PlayerControlMessage {
     ButtonPressed(ButtonEvent),
     PosSliderChanged(f32),
     PosSliderReleased(),
-    ContinuousPosUpdate(Instant),
+    /// Lines the wheel was scrolled up over the slider
+    PosSliderScrolled(f32),
+    JumpInputChanged(String),
+    JumpSubmitted(),
+    /// Sent by the timer while playing, to move the slider along
+    Tick(),
+    /// Where the cursor is over the slider, as a fraction of its width
+    PosSliderHovered(Option<f32>),
+    StoryboardLoaded(String, Option<Storyboard>),
+    /// Thumbnail of a storyboard frame of the track with the uri
+    PreviewLoaded(String, u32, iced::image::Handle),
+    SleepSelected(SleepOption),
+    SleepInputChanged(String),
+    SleepSubmitted(),
 }
 
 #[derive(Debug, Clone)]
@@ -24,20 +56,86 @@ pub enum ButtonEvent {
     PlayPause,
     Next,
     Repeat,
+    Rewind,
+    Forward,
+    /// Seeks relative to the current position, in seconds
+    Seek(i64),
+    /// Sets the start of the loop, then its end, then clears it
+    Loop,
+}
+
+/// Choices of the sleep timer, which pauses the bot when it runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepOption {
+    Off,
+    Minutes(u64),
+    EndOfTrack,
+    /// Asks for the minutes in an input next to the menu
+    Custom,
+}
+
+impl SleepOption {
+    pub const ALL: &[SleepOption] = &[
+        SleepOption::Off,
+        SleepOption::Minutes(15),
+        SleepOption::Minutes(30),
+        SleepOption::Minutes(45),
+        SleepOption::Minutes(60),
+        SleepOption::Minutes(90),
+        SleepOption::EndOfTrack,
+        SleepOption::Custom,
+    ];
+}
+
+impl Display for SleepOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SleepOption::Off => write!(f, "No sleep timer"),
+            SleepOption::Minutes(m) => write!(f, "{} min", m),
+            SleepOption::EndOfTrack => write!(f, "End of track"),
+            SleepOption::Custom => write!(f, "Custom..."),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct PlayerControl {
     cur_song: Option<CompTrack>,
     player_state: Option<PlayerState>,
+    clock: PlaybackClock,
     user_sliding: bool,
     slider_pos: f32,
+    rewind_step: Duration,
+    forward_step: Duration,
+    /// Wheel movement not yet turned into a seek
+    scroll_lines: f32,
+    jump_value: String,
+    hover_pos: Option<f32>,
+    storyboard: Option<Storyboard>,
+    /// The storyboard of the current track was asked for, it is fetched on the first hover
+    storyboard_requested: bool,
+    /// Last frame requested for the preview and the last one loaded
+    preview_frame: Option<u32>,
+    preview: Option<iced::image::Handle>,
+    sleep_option: SleepOption,
+    /// When the sleep timer pauses, unset while it is off or waits for the end of the track
+    sleep_deadline: Option<Instant>,
+    sleep_value: String,
+    /// Section the client keeps seeking back into, the end is only set after the start
+    loop_start: Option<Duration>,
+    loop_end: Option<Duration>,
 
     song_pos_slider: iced::slider::State,
+    jump_input: iced::text_input::State,
+    sleep_pick_list: iced::pick_list::State<SleepOption>,
+    sleep_input: iced::text_input::State,
     prev_button_state: iced::button::State,
+    rewind_button_state: iced::button::State,
+    forward_button_state: iced::button::State,
     play_pause_button_state: iced::button::State,
     next_button_state: iced::button::State,
     repeat_button_state: iced::button::State,
+    loop_button_state: iced::button::State,
 }
 
 impl PlayerControl {
@@ -46,16 +144,56 @@ impl PlayerControl {
         PlayerControl {
             cur_song: None,
             player_state: None,
+            clock: PlaybackClock::new(),
             user_sliding: false,
             slider_pos: 0.0,
+            rewind_step: Duration::from_secs(DEFAULT_SEEK_STEP),
+            forward_step: Duration::from_secs(DEFAULT_SEEK_STEP),
+            scroll_lines: 0.0,
+            jump_value: String::new(),
+            hover_pos: None,
+            storyboard: None,
+            storyboard_requested: false,
+            preview_frame: None,
+            preview: None,
+            sleep_option: SleepOption::Off,
+            sleep_deadline: None,
+            sleep_value: String::new(),
+            loop_start: None,
+            loop_end: None,
             song_pos_slider: Default::default(),
+            jump_input: Default::default(),
+            sleep_pick_list: Default::default(),
+            sleep_input: Default::default(),
             prev_button_state: Default::default(),
+            rewind_button_state: Default::default(),
+            forward_button_state: Default::default(),
             play_pause_button_state: Default::default(),
             next_button_state: Default::default(),
             repeat_button_state: Default::default(),
+            loop_button_state: Default::default(),
         }
     }
 
+    /// True while the position moves, so the timer is needed
+    pub fn is_playing(&self) -> bool {
+        self.cur_song.is_some() && self.clock.is_playing()
+    }
+
+    /// True while the sleep timer counts down, so the timer is needed even while paused
+    pub fn is_sleep_timer_running(&self) -> bool {
+        self.sleep_deadline.is_some()
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.cur_song.as_ref().map(|t| t.is_live()).unwrap_or(false)
+    }
+
+    pub fn set_seek_steps(&mut self, rewind: Duration, forward: Duration) {
+        self.rewind_step = rewind;
+        self.forward_step = forward;
+    }
+
     pub fn update(
         &mut self,
         con: &Option<Connection>,
@@ -69,19 +207,10 @@ impl PlayerControl {
                 }
             }
             PlayerControlMessage::PosSliderReleased() => {
-                if self.cur_song.is_some() {
-                    let _target = self.slider_pos;
-                    self.user_sliding = false;
-                }
-
-                if let Some(con) = con {
-                    if let Some(track) = self.cur_song.as_ref() {
-                        return con.control_request(
-                            reciprocity_communication::messages::PlayerControl::SetTime(
-                                track.pos(self.slider_pos),
-                            ),
-                        );
-                    }
+                self.user_sliding = false;
+                if let Some(track) = self.cur_song.as_ref() {
+                    let target = track.pos(self.slider_pos);
+                    return self.set_time(con, target);
                 }
             }
             PlayerControlMessage::PlayerStateChanged(state) => {
@@ -92,61 +221,100 @@ impl PlayerControl {
                     .as_ref()
                     .map(|s| s.current.clone())
                     .flatten();
-                //If bot are Some
-                if let (Some(new), Some(old)) = (new.clone(), self.cur_song.as_mut()) {
-                    if new.uri.eq(&old.track.uri) {
-                        old.track = new;
-                        old.updated = Instant::now();
-                    } else {
-                        self.cur_song = CompTrack {
-                            track: new,
-                            updated: Instant::now(),
-                            image: None,
+                let paused = self.player_state.as_ref().map(|s| s.paused).unwrap_or(true);
+                let mut commands = Vec::new();
+                match (new, self.cur_song.as_mut()) {
+                    (Some(new), Some(old)) if new.uri.eq(&old.track.uri) => old.track = new,
+                    (new, old) => {
+                        //Streams have no end to wait for and skipped tracks end early, so the
+                        //next track just started and this is as close to the boundary as it gets
+                        if old.is_some() && self.sleep_option == SleepOption::EndOfTrack {
+                            self.sleep_option = SleepOption::Off;
+                            if new.is_some() && !paused {
+                                commands.push(self.sleep(con));
+                            }
                         }
-                        .into();
-                    }
-                } else if let Some(new) = new {
-                    self.cur_song = CompTrack {
-                        track: new,
-                        updated: Instant::now(),
-                        image: None,
+                        self.cur_song = new.map(CompTrack::new);
+                        self.clock = PlaybackClock::new();
+                        self.loop_start = None;
+                        self.loop_end = None;
+                        self.storyboard = None;
+                        self.storyboard_requested = false;
+                        self.preview_frame = None;
+                        self.preview = None;
                     }
-                    .into();
-                } else {
-                    self.cur_song = None;
                 }
 
-                if !self.user_sliding {
-                    self.slider_pos = self
-                        .cur_song
-                        .as_ref()
-                        .map(|t| t.pos_percentage())
-                        .unwrap_or(0.0);
-                }
+                let latency = con.as_ref().map(|c| c.latency()).unwrap_or_default();
                 if let Some(n) = self.cur_song.as_ref() {
-                    let update_cmd = get_update_command(n.updated);
+                    self.clock.sync(n.track.pos, n.track.len, paused, latency);
+                }
+                self.update_slider();
 
+                if let Some(n) = self.cur_song.as_ref() {
                     if n.image.is_none() {
-                        let download_cmd = Command::perform(n.clone().download_image(), |res| {
+                        commands.push(Command::perform(n.clone().download_image(), |res| {
                             Message::PlayerControl(PlayerControlMessage::SongImageUpdated(
-                                res.map(Some).map_err(|e| println!("{}", e)).unwrap_or(None),
+                                res.map(Some).map_err(|e| warn!("{}", e)).unwrap_or(None),
                             ))
-                        });
-                        return Command::batch(vec![update_cmd, download_cmd]);
+                        }));
                     }
-                    return update_cmd;
                 }
+                return Command::batch(commands);
+            }
+            PlayerControlMessage::PosSliderScrolled(lines) => {
+                self.scroll_lines += lines;
+                let notches = self.scroll_lines.trunc();
+                if notches == 0.0 {
+                    return Command::none();
+                }
+                self.scroll_lines -= notches;
+                return match notches > 0.0 {
+                    true => self.seek_by(con, self.forward_step * notches as u32, false),
+                    false => self.seek_by(con, self.rewind_step * (-notches) as u32, true),
+                };
+            }
+            PlayerControlMessage::JumpInputChanged(value) => self.jump_value = value,
+            PlayerControlMessage::JumpSubmitted() => match duration_parse(&self.jump_value) {
+                Some(target) => {
+                    self.jump_value.clear();
+                    self.jump_input.unfocus();
+                    return self.set_time(con, target);
+                }
+                None => {
+                    return toast::show(Toast::warning(format!(
+                        "Invalid time \"{}\", expected mm:ss",
+                        self.jump_value
+                    )));
+                }
+            },
+            PlayerControlMessage::ButtonPressed(ButtonEvent::Rewind) => {
+                return self.seek_by(con, self.rewind_step, true);
+            }
+            PlayerControlMessage::ButtonPressed(ButtonEvent::Forward) => {
+                return self.seek_by(con, self.forward_step, false);
             }
+            PlayerControlMessage::ButtonPressed(ButtonEvent::Seek(secs)) => {
+                return self.seek_by(con, Duration::from_secs(secs.unsigned_abs()), secs < 0);
+            }
+            PlayerControlMessage::ButtonPressed(ButtonEvent::Loop) => return self.cycle_loop(),
             PlayerControlMessage::ButtonPressed(b) => {
                 if let Some(state) = self.player_state.as_ref() {
                     if let Some(con) = con {
                         let req = match b {
+                            //Seeking and looping are handled above
+                            ButtonEvent::Rewind
+                            | ButtonEvent::Forward
+                            | ButtonEvent::Seek(_)
+                            | ButtonEvent::Loop => return Command::none(),
                             ButtonEvent::Prev => ControlRequest::BackSkip(1),
                             ButtonEvent::PlayPause => match state.paused {
                                 true => ControlRequest::Resume(),
                                 false => ControlRequest::Pause(),
                             },
                             ButtonEvent::Next => ControlRequest::Skip(1),
+                            //Streams never end, so there is nothing to repeat
+                            ButtonEvent::Repeat if self.is_live() => return Command::none(),
                             ButtonEvent::Repeat => match state.mode {
                                 PlayMode::Normal => ControlRequest::PlayMode(PlayMode::LoopAll),
                                 PlayMode::LoopAll => ControlRequest::PlayMode(PlayMode::LoopOne),
@@ -164,22 +332,69 @@ impl PlayerControl {
                     }
                 }
             }
-            PlayerControlMessage::ContinuousPosUpdate(when) => {
-                if let Some(state) = self.player_state.as_ref() {
-                    if !state.paused {
-                        if let Some(song) = self.cur_song.as_ref() {
-                            if song.updated.eq(&when) {
-                                if !self.user_sliding {
-                                    self.slider_pos = self
-                                        .cur_song
-                                        .as_ref()
-                                        .map(|t| t.pos_percentage())
-                                        .unwrap_or(0.0);
-                                }
-
-                                return get_update_command(when);
-                            }
-                        }
+            PlayerControlMessage::Tick() => {
+                self.update_slider();
+                if let (Some(start), Some(end)) = (self.loop_start, self.loop_end) {
+                    if self.clock.position() >= end {
+                        return self.set_time(con, start);
+                    }
+                }
+                if let Some(deadline) = self.sleep_deadline {
+                    if Instant::now() >= deadline {
+                        self.sleep_option = SleepOption::Off;
+                        self.sleep_deadline = None;
+                        return self.sleep(con);
+                    }
+                }
+                if self.sleep_option == SleepOption::EndOfTrack && self.is_track_ending(con) {
+                    self.sleep_option = SleepOption::Off;
+                    return self.sleep(con);
+                }
+            }
+            PlayerControlMessage::PosSliderHovered(pos) => {
+                self.hover_pos = pos;
+                //Most tracks are never hovered, so the storyboard is only fetched once needed
+                if pos.is_some() && !self.storyboard_requested {
+                    return self.load_storyboard();
+                }
+                return self.load_preview();
+            }
+            PlayerControlMessage::StoryboardLoaded(uri, storyboard) => {
+                if self.is_current(&uri) {
+                    self.storyboard = storyboard;
+                    return self.load_preview();
+                }
+            }
+            PlayerControlMessage::PreviewLoaded(uri, frame, img) => {
+                if self.is_current(&uri) && self.preview_frame == Some(frame) {
+                    self.preview = Some(img);
+                }
+            }
+            PlayerControlMessage::SleepSelected(option) => {
+                self.sleep_option = option;
+                self.sleep_deadline = match option {
+                    SleepOption::Minutes(m) => Some(Instant::now() + Duration::from_secs(m * 60)),
+                    _ => None,
+                };
+                if option == SleepOption::Custom {
+                    self.sleep_input.focus();
+                }
+            }
+            PlayerControlMessage::SleepInputChanged(value) => self.sleep_value = value,
+            PlayerControlMessage::SleepSubmitted() => {
+                match self.sleep_value.trim().parse::<u64>() {
+                    Ok(m) if SLEEP_MINUTES.contains(&m) => {
+                        self.sleep_value.clear();
+                        self.sleep_input.unfocus();
+                        self.sleep_deadline = Some(Instant::now() + Duration::from_secs(m * 60));
+                    }
+                    _ => {
+                        return toast::show(Toast::warning(format!(
+                            "Invalid sleep timer \"{}\", expected {} to {} minutes",
+                            self.sleep_value,
+                            SLEEP_MINUTES.start(),
+                            SLEEP_MINUTES.end()
+                        )));
                     }
                 }
             }
@@ -188,6 +403,152 @@ impl PlayerControl {
         Command::none()
     }
 
+    fn cycle_loop(&mut self) -> Command<Message> {
+        if self.cur_song.is_none() || self.is_live() {
+            return Command::none();
+        }
+        let pos = self.clock.position();
+        let text = match (self.loop_start, self.loop_end) {
+            (None, _) => {
+                self.loop_start = Some(pos);
+                format!("Loop start set to {}", duration_fmt(&pos))
+            }
+            (Some(start), None) => {
+                //Both ends at once would seek on every tick
+                if pos == start {
+                    return Command::none();
+                }
+                let (start, end) = (start.min(pos), start.max(pos));
+                self.loop_start = Some(start);
+                self.loop_end = Some(end);
+                format!("Looping {} - {}", duration_fmt(&start), duration_fmt(&end))
+            }
+            (Some(_), Some(_)) => {
+                self.loop_start = None;
+                self.loop_end = None;
+                String::from("Loop cleared")
+            }
+        };
+        toast::show(Toast::info(text))
+    }
+
+    /// True while the current track plays its last moments before the bot moves on
+    fn is_track_ending(&self, con: &Option<Connection>) -> bool {
+        let len = match self.cur_song.as_ref() {
+            Some(track) if !track.is_live() => track.track.len,
+            _ => return false,
+        };
+        let latency = con.as_ref().map(|c| c.latency()).unwrap_or_default();
+        self.is_playing() && self.clock.position() + END_OF_TRACK_LEAD + latency >= len
+    }
+
+    /// Pauses the bot for the sleep timer
+    fn sleep(&self, con: &Option<Connection>) -> Command<Message> {
+        let con = match con {
+            Some(con) => con,
+            None => return Command::none(),
+        };
+        Command::batch(vec![
+            con.control_request(ControlRequest::Pause()),
+            toast::show(Toast::info(String::from("Sleep timer ran out, paused"))),
+        ])
+    }
+
+    fn is_current(&self, uri: &str) -> bool {
+        self.cur_song
+            .as_ref()
+            .map(|t| t.track.uri.eq(uri))
+            .unwrap_or(false)
+    }
+
+    /// Looks up the preview thumbnails of the current track, if it is a YouTube video
+    fn load_storyboard(&mut self) -> Command<Message> {
+        self.storyboard_requested = true;
+        let track = match self.cur_song.as_ref() {
+            Some(track) if !track.is_live() => track.track.clone(),
+            _ => return Command::none(),
+        };
+        let id = match get_yt_identifier(track.uri.clone()) {
+            Ok(id) => id,
+            Err(_) => return Command::none(),
+        };
+        Command::perform(Storyboard::fetch(id, track.len), move |res| {
+            let storyboard = res.map_err(|e| debug!("{}", e)).ok();
+            Message::PlayerControl(PlayerControlMessage::StoryboardLoaded(
+                track.uri.clone(),
+                storyboard,
+            ))
+        })
+    }
+
+    /// Requests the thumbnail for the hovered position, unless it is already shown or on its way
+    fn load_preview(&mut self) -> Command<Message> {
+        let (track, storyboard, pos) = match (
+            self.cur_song.as_ref(),
+            self.storyboard.as_ref(),
+            self.hover_pos,
+        ) {
+            (Some(track), Some(storyboard), Some(pos)) => (track, storyboard, pos),
+            _ => return Command::none(),
+        };
+        let frame = storyboard.frame(track.pos(pos * 100.0));
+        if self.preview_frame == Some(frame) {
+            return Command::none();
+        }
+        self.preview_frame = Some(frame);
+        let uri = track.track.uri.clone();
+        Command::perform(storyboard.clone().thumbnail(frame), move |res| match res {
+            Ok(img) => {
+                Message::PlayerControl(PlayerControlMessage::PreviewLoaded(uri.clone(), frame, img))
+            }
+            Err(e) => {
+                debug!("{}", e);
+                Message::None()
+            }
+        })
+    }
+
+    /// Seeks the offset before or after the shown position
+    fn seek_by(
+        &mut self,
+        con: &Option<Connection>,
+        offset: Duration,
+        back: bool,
+    ) -> Command<Message> {
+        if self.cur_song.is_none() {
+            return Command::none();
+        }
+        let pos = self.clock.position();
+        let target = match back {
+            true => pos.checked_sub(offset).unwrap_or_default(),
+            false => pos + offset,
+        };
+        self.set_time(con, target)
+    }
+
+    /// Seeks to the target, clamped to the current track
+    fn set_time(&mut self, con: &Option<Connection>, target: Duration) -> Command<Message> {
+        let (con, track) = match (con, self.cur_song.as_ref()) {
+            (Some(con), Some(track)) if !track.is_live() => (con, track),
+            _ => return Command::none(),
+        };
+        let target = target.min(track.track.len);
+        //Show the target right away, so seeking again starts from there
+        self.clock.seek(target);
+        self.update_slider();
+        con.control_request(ControlRequest::SetTime(target))
+    }
+
+    fn update_slider(&mut self) {
+        if !self.user_sliding {
+            self.slider_pos = self
+                .cur_song
+                .as_ref()
+                .map(|t| t.percentage(self.clock.position()))
+                .unwrap_or(0.0);
+        }
+    }
+
     pub fn view(&mut self, theme: &Theme) -> (Element<'_, Message>, Element<'_, Message>) {
         let img = self
             .cur_song
@@ -208,11 +569,16 @@ impl PlayerControl {
             .as_ref()
             .map(|t| t.track.title.as_str())
             .unwrap_or("No Song");
-        let song_pos = self
-            .cur_song
-            .as_ref()
-            .map(|t| duration_fmt(&t.pos(self.slider_pos)))
-            .unwrap_or_else(|| String::from("-:--"));
+        let live = self.is_live();
+        //Streams show the time since they started playing instead
+        let song_pos = match live {
+            true => duration_fmt(&self.clock.position()),
+            false => self
+                .cur_song
+                .as_ref()
+                .map(|t| duration_fmt(&t.pos(self.slider_pos)))
+                .unwrap_or_else(|| String::from("-:--")),
+        };
         let song_len = self
             .cur_song
             .as_ref()
@@ -227,6 +593,16 @@ impl PlayerControl {
             ButtonEvent::Prev,
         )))
         .style(theme.control_button_theme());
+        let mut rewind_btn = Button::new(
+            &mut self.rewind_button_state,
+            Icon::FastRewind.get_svg(theme),
+        )
+        .style(theme.control_button_theme());
+        let mut forward_btn = Button::new(
+            &mut self.forward_button_state,
+            Icon::FastForward.get_svg(theme),
+        )
+        .style(theme.control_button_theme());
         let play_pause_icon: Icon = self.player_state.as_ref().map(|s| !s.paused).into();
         let play_pause_btn = Button::new(
             &mut self.play_pause_button_state,
@@ -242,11 +618,75 @@ impl PlayerControl {
             )))
             .style(theme.control_button_theme());
         let repeat_icon: Icon = self.player_state.as_ref().map(|s| s.mode.clone()).into();
-        let repeat_btn = Button::new(&mut self.repeat_button_state, repeat_icon.get_svg(theme))
-            .on_press(Message::PlayerControl(PlayerControlMessage::ButtonPressed(
-                ButtonEvent::Repeat,
-            )))
+        let mut repeat_btn = Button::new(&mut self.repeat_button_state, repeat_icon.get_svg(theme))
             .style(theme.control_button_theme());
+        let loop_label = match (self.loop_start, self.loop_end) {
+            (None, _) => "A",
+            (Some(_), None) => "B",
+            (Some(_), Some(_)) => "A-B",
+        };
+        let mut loop_btn = Button::new(
+            &mut self.loop_button_state,
+            Container::new(Text::new(loop_label).size(14).color(theme.text_color()))
+                .width(Length::Units(30))
+                .height(Length::Fill)
+                .center_x()
+                .center_y(),
+        )
+        .style(theme.control_button_theme());
+        //Buttons without a message are disabled
+        if !live {
+            rewind_btn = rewind_btn.on_press(Message::PlayerControl(
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Rewind),
+            ));
+            forward_btn = forward_btn.on_press(Message::PlayerControl(
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Forward),
+            ));
+            repeat_btn = repeat_btn.on_press(Message::PlayerControl(
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Repeat),
+            ));
+            loop_btn = loop_btn.on_press(Message::PlayerControl(
+                PlayerControlMessage::ButtonPressed(ButtonEvent::Loop),
+            ));
+        }
+
+        let sleep_pick_list = PickList::new(
+            &mut self.sleep_pick_list,
+            SleepOption::ALL,
+            Some(self.sleep_option),
+            |o| Message::PlayerControl(PlayerControlMessage::SleepSelected(o)),
+        )
+        .text_size(14)
+        .width(Length::Units(120))
+        .style(theme.pick_list_theme());
+        let sleep_status: Element<'_, Message> = match (self.sleep_option, self.sleep_deadline) {
+            (_, Some(deadline)) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                Text::new(duration_fmt(&remaining))
+                    .size(14)
+                    .color(theme.text_color())
+                    .into()
+            }
+            (SleepOption::Custom, None) => {
+                TextInput::new(&mut self.sleep_input, "min", &self.sleep_value, |v| {
+                    Message::PlayerControl(PlayerControlMessage::SleepInputChanged(v))
+                })
+                .on_submit(Message::PlayerControl(
+                    PlayerControlMessage::SleepSubmitted(),
+                ))
+                .size(14)
+                .padding(5)
+                .width(Length::Units(50))
+                .style(theme.search_input_theme())
+                .into()
+            }
+            _ => Space::new(Length::Shrink, Length::Shrink).into(),
+        };
+        let sleep = Row::new()
+            .push(sleep_pick_list)
+            .push(sleep_status)
+            .spacing(5)
+            .align_items(Align::Center);
 
         let mut row = Row::new();
         row = row
@@ -255,9 +695,13 @@ impl PlayerControl {
                     .width(Length::Units(190)),
             )
             .push(prev_btn)
+            .push(rewind_btn)
             .push(play_pause_btn)
+            .push(forward_btn)
             .push(next_btn)
             .push(repeat_btn)
+            .push(loop_btn)
+            .push(sleep)
             //.push(Space::new(Length::Units(15), Length::Fill))
             .push(
                 Container::new(Text::new(song_pos).size(16).color(theme.text_color()))
@@ -267,6 +711,17 @@ impl PlayerControl {
             .push(Space::new(Length::Units(10), Length::Fill))
             .height(Length::Units(35));
 
+        if live {
+            let badge = Container::new(Text::new("LIVE").size(14))
+                .padding(3)
+                .style(theme.live_badge_theme());
+            row = row
+                .push(badge)
+                .push(Space::new(Length::Fill, Length::Shrink))
+                .align_items(Align::Center);
+            return (song_picture.into(), Container::new(row).padding(5).into());
+        }
+
         let slid = Slider::new(
             &mut self.song_pos_slider,
             0.0..=100.0,
@@ -278,6 +733,43 @@ impl PlayerControl {
             PlayerControlMessage::PosSliderReleased(),
         ))
         .style(theme.song_slider_theme());
+        let loop_markers = match self.cur_song.as_ref() {
+            Some(track) => [self.loop_start, self.loop_end]
+                .iter()
+                .flatten()
+                .map(|pos| track.percentage(*pos) / 100.0)
+                .collect(),
+            None => Vec::new(),
+        };
+        let slid: Element<'_, Message> = Markers::new(slid, loop_markers, theme.accent_color())
+            .shade_range(self.loop_end.is_some())
+            .into();
+        let mut slid = HoverArea::new(slid, self.hover_pos, |pos| {
+            Message::PlayerControl(PlayerControlMessage::PosSliderHovered(pos))
+        });
+        if let (Some(track), Some(pos)) = (self.cur_song.as_ref(), self.hover_pos) {
+            let mut preview = Column::new().spacing(3).align_items(Align::Center);
+            if let Some(img) = self.preview.clone() {
+                preview = preview.push(Image::new(img).width(Length::Units(160)));
+            }
+            preview = preview.push(Text::new(duration_fmt(&track.pos(pos * 100.0))).size(14));
+            slid = slid.preview(
+                Container::new(preview)
+                    .padding(4)
+                    .style(theme.seek_preview_theme()),
+            );
+        }
+        let slid = WheelArea::new(slid, |lines| {
+            Message::PlayerControl(PlayerControlMessage::PosSliderScrolled(lines))
+        });
+        let jump_input = TextInput::new(&mut self.jump_input, "mm:ss", &self.jump_value, |v| {
+            Message::PlayerControl(PlayerControlMessage::JumpInputChanged(v))
+        })
+        .on_submit(Message::PlayerControl(PlayerControlMessage::JumpSubmitted()))
+        .size(16)
+        .padding(5)
+        .width(Length::Units(70))
+        .style(theme.search_input_theme());
         row = row
             .push(slid)
             .push(Space::new(Length::Units(10), Length::Fill))
@@ -286,16 +778,11 @@ impl PlayerControl {
                     .width(Length::Units(70))
                     .align_x(Align::Start),
             )
+            .push(jump_input)
+            .push(Space::new(Length::Units(10), Length::Fill))
             .align_items(Align::Center);
         let control_element = Container::new(row).padding(5);
 
         (song_picture.into(), control_element.into())
     }
 }
-
-fn get_update_command(when: Instant) -> Command<Message> {
-    Command::perform(
-        async { tokio::time::sleep(Duration::from_millis(500)).await },
-        move |_| Message::PlayerControl(PlayerControlMessage::ContinuousPosUpdate(when)),
-    )
-}
@@ -0,0 +1,539 @@
+use crate::library::{SavedPlaylist, SavedTrack};
+use crate::tabs::library::LibraryMessage;
+use crate::toast::Toast;
+use crate::util;
+use crate::Message;
+use iced::Command;
+use reqwest::Url;
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Version written into exported JSON playlists, bumped on incompatible changes
+const JSON_VERSION: u32 = 1;
+const XSPF_NAMESPACE: &str = "http://xspf.org/ns/0/";
+
+pub const EXPORT_FILTERS: [(&str, &[&str]); 3] = [
+    ("M3U8 Playlist", &["m3u8"]),
+    ("XSPF Playlist", &["xspf"]),
+    ("JSON Playlist", &["json"]),
+];
+pub const IMPORT_EXTENSIONS: [&str; 4] = ["m3u8", "m3u", "xspf", "json"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    M3u8,
+    Xspf,
+    Json,
+}
+
+impl Format {
+    /// Format of the file by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "m3u8" | "m3u" => Some(Format::M3u8),
+            "xspf" => Some(Format::Xspf),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::M3u8 => "m3u8",
+            Format::Xspf => "xspf",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Playlist read from a file, with the entries which could not be read
+#[derive(Debug, Clone)]
+pub struct Imported {
+    pub playlist: SavedPlaylist,
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonPlaylist {
+    version: u32,
+    name: String,
+    /// Read one by one, so a broken track does not fail the whole playlist
+    #[serde(default)]
+    tracks: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonTrack {
+    uri: String,
+    #[serde(default)]
+    title: String,
+    /// Milliseconds, 0 for streams
+    #[serde(default)]
+    length: u64,
+}
+
+/// Writes the playlist in the format
+pub fn write(format: Format, playlist: &SavedPlaylist) -> Result<String, String> {
+    match format {
+        Format::M3u8 => Ok(write_m3u8(playlist)),
+        Format::Xspf => Ok(write_xspf(playlist)),
+        Format::Json => {
+            let tracks = playlist
+                .tracks
+                .iter()
+                .map(|t| {
+                    serde_json::to_value(JsonTrack {
+                        uri: t.uri.clone(),
+                        title: t.title.clone(),
+                        length: t.len.as_millis() as u64,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            let json = JsonPlaylist {
+                version: JSON_VERSION,
+                name: playlist.name.clone(),
+                tracks,
+            };
+            serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Reads a playlist in the format, skipping the entries which are not supported
+pub fn parse(format: Format, content: &str) -> Result<Imported, String> {
+    match format {
+        Format::M3u8 => Ok(parse_m3u8(content)),
+        Format::Xspf => parse_xspf(content),
+        Format::Json => parse_json(content),
+    }
+}
+
+fn write_m3u8(playlist: &SavedPlaylist) -> String {
+    let mut lines = vec![
+        String::from("#EXTM3U"),
+        format!("#PLAYLIST:{}", playlist.name),
+    ];
+    for track in playlist.tracks.iter() {
+        //Streams have no length, which is -1 in M3U
+        let secs = match util::is_live(&track.track()) {
+            true => -1,
+            false => track.len.as_secs() as i64,
+        };
+        lines.push(format!("#EXTINF:{},{}", secs, track.title));
+        lines.push(track.uri.clone());
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn parse_m3u8(content: &str) -> Imported {
+    let mut playlist = SavedPlaylist {
+        name: String::new(),
+        tracks: Vec::new(),
+    };
+    let mut problems = Vec::new();
+    //Title and length of the next location
+    let mut info: Option<(Duration, String)> = None;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+            playlist.name = name.trim().to_string();
+        } else if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            let mut parts = extinf.splitn(2, ',');
+            //The length can be followed by attributes
+            let secs = parts
+                .next()
+                .and_then(|s| s.split_whitespace().next())
+                .and_then(|s| s.parse::<i64>().ok());
+            match (secs, parts.next()) {
+                (Some(secs), Some(title)) => {
+                    let len = Duration::from_secs(secs.max(0) as u64);
+                    info = Some((len, title.trim().to_string()));
+                }
+                _ => problems.push(format!("Line {}: Invalid #EXTINF: {}", i + 1, extinf)),
+            }
+        } else if line.is_empty() || line.starts_with('#') {
+            //Other directives and comments
+        } else {
+            let (len, title) = info.take().unwrap_or_default();
+            match check_uri(line) {
+                Ok(()) => playlist.tracks.push(SavedTrack {
+                    uri: line.to_string(),
+                    title: match title.is_empty() {
+                        true => line.to_string(),
+                        false => title,
+                    },
+                    len,
+                }),
+                Err(e) => problems.push(format!("Line {}: {}", i + 1, e)),
+            }
+        }
+    }
+    Imported { playlist, problems }
+}
+
+fn write_xspf(playlist: &SavedPlaylist) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <playlist version=\"1\" xmlns=\"{}\">\n  <title>{}</title>\n  <trackList>\n",
+        XSPF_NAMESPACE,
+        xml_escape(&playlist.name)
+    );
+    for track in playlist.tracks.iter() {
+        xml.push_str("    <track>\n");
+        xml.push_str(&format!(
+            "      <location>{}</location>\n",
+            xml_escape(&track.uri)
+        ));
+        xml.push_str(&format!(
+            "      <title>{}</title>\n",
+            xml_escape(&track.title)
+        ));
+        if !util::is_live(&track.track()) {
+            xml.push_str(&format!(
+                "      <duration>{}</duration>\n",
+                track.len.as_millis()
+            ));
+        }
+        xml.push_str("    </track>\n");
+    }
+    xml.push_str("  </trackList>\n</playlist>\n");
+    xml
+}
+
+/// Reads the elements of XSPF used for tracks, which are `location`, `title` and `duration`.
+/// Tags are matched by their local name, so prefixed ones are read as well
+fn parse_xspf(content: &str) -> Result<Imported, String> {
+    let doc = Document::parse(content).map_err(|e| format!("Invalid playlist: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "playlist" {
+        return Err(String::from("Invalid playlist: No <playlist> element"));
+    }
+    let mut playlist = SavedPlaylist {
+        name: xml_child(root, "title").map(xml_text).unwrap_or_default(),
+        tracks: Vec::new(),
+    };
+    let mut problems = Vec::new();
+
+    let tracks = xml_child(root, "trackList")
+        .into_iter()
+        .flat_map(|list| list.children())
+        .filter(|n| n.is_element() && n.tag_name().name() == "track");
+    for track in tracks {
+        let line = doc.text_pos_at(track.range().start).row;
+        let location = match xml_child(track, "location").map(xml_text) {
+            Some(location) if !location.is_empty() => location,
+            _ => {
+                problems.push(format!("Line {}: Track without a location", line));
+                continue;
+            }
+        };
+        if let Err(e) = check_uri(&location) {
+            problems.push(format!("Line {}: {}", line, e));
+            continue;
+        }
+        let title = xml_child(track, "title")
+            .map(xml_text)
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| location.clone());
+        let len = match xml_child(track, "duration").map(|d| xml_text(d).parse::<u64>()) {
+            Some(Ok(ms)) => Duration::from_millis(ms),
+            Some(Err(_)) => {
+                problems.push(format!(
+                    "Line {}: Invalid duration, imported without a length",
+                    line
+                ));
+                Duration::default()
+            }
+            None => Duration::default(),
+        };
+        playlist.tracks.push(SavedTrack {
+            uri: location,
+            title,
+            len,
+        });
+    }
+    Ok(Imported { playlist, problems })
+}
+
+fn parse_json(content: &str) -> Result<Imported, String> {
+    let json: JsonPlaylist =
+        serde_json::from_str(content).map_err(|e| format!("Invalid playlist: {}", e))?;
+    if json.version > JSON_VERSION {
+        return Err(format!(
+            "Playlist version {} is newer than the supported version {}",
+            json.version, JSON_VERSION
+        ));
+    }
+    let mut playlist = SavedPlaylist {
+        name: json.name,
+        tracks: Vec::new(),
+    };
+    let mut problems = Vec::new();
+    for (i, track) in json.tracks.into_iter().enumerate() {
+        let track = serde_json::from_value::<JsonTrack>(track)
+            .map_err(|e| e.to_string())
+            .and_then(|t| check_uri(&t.uri).map(|_| t));
+        match track {
+            Ok(track) => playlist.tracks.push(SavedTrack {
+                title: match track.title.is_empty() {
+                    true => track.uri.clone(),
+                    false => track.title,
+                },
+                uri: track.uri,
+                len: Duration::from_millis(track.length),
+            }),
+            Err(e) => problems.push(format!("Track {}: {}", i + 1, e)),
+        }
+    }
+    Ok(Imported { playlist, problems })
+}
+
+/// Only web links can be played by the bot, no local files
+fn check_uri(uri: &str) -> Result<(), String> {
+    match Url::parse(uri) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+        Ok(_) => Err(format!("Unsupported location: {}", uri)),
+        Err(_) => Err(format!("Invalid url: {}", uri)),
+    }
+}
+
+/// First child element with the local name, whatever its namespace
+fn xml_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Text of an element, with its entities and CDATA sections resolved by the parser
+fn xml_text(node: Node) -> String {
+    let text: String = node
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect();
+    text.trim().to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Name of the playlist usable as file name
+fn file_name(name: &str, format: Format) -> String {
+    let name: String = name
+        .chars()
+        .map(
+            |c| match c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect();
+    format!("{}.{}", name.trim(), format.extension())
+}
+
+async fn save(playlist: SavedPlaylist, path: PathBuf) -> Result<PathBuf, String> {
+    //Files without a known extension are written as M3U8
+    let (format, path) = match Format::from_path(&path) {
+        Some(format) => (format, path),
+        None => (Format::M3u8, path.with_extension(Format::M3u8.extension())),
+    };
+    let content = write(format, &playlist)?;
+    File::create(&path)
+        .and_then(|mut f| f.write_all(content.as_bytes()))
+        .map_err(|e| format!("Could not write {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+async fn load(path: PathBuf) -> Result<Imported, String> {
+    let format =
+        Format::from_path(&path).ok_or_else(|| format!("Unsupported playlist file {:?}", path))?;
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Could not read {:?}: {}", path, e))?;
+    let mut imported = parse(format, &content)?;
+    if imported.playlist.name.trim().is_empty() {
+        imported.playlist.name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("Imported"));
+    }
+    Ok(imported)
+}
+
+/// Asks where to export the playlist to, the format is taken from the extension
+pub fn export(playlist: SavedPlaylist) -> Command<Message> {
+    Command::perform(
+        async move {
+            let name = file_name(&playlist.name, Format::M3u8);
+            match util::dialog::save_path_filters(&EXPORT_FILTERS, name).await {
+                Some(path) => save(playlist, path).await.map(Some),
+                None => Ok(None),
+            }
+        },
+        |res| match res {
+            Ok(Some(path)) => Message::ShowToast(Toast::success(format!("Exported to {:?}", path))),
+            Ok(None) => Message::None(),
+            Err(e) => Message::ShowToast(Toast::warning(e)),
+        },
+    )
+}
+
+/// Asks which playlist file to import
+pub fn import() -> Command<Message> {
+    Command::perform(
+        async {
+            match util::dialog::open_path("Playlist", &IMPORT_EXTENSIONS).await {
+                Some(path) => load(path).await.map(Some),
+                None => Ok(None),
+            }
+        },
+        |res| Message::Library(LibraryMessage::Imported(res)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist() -> SavedPlaylist {
+        SavedPlaylist {
+            name: String::from("Mix & <Match>"),
+            tracks: vec![
+                SavedTrack {
+                    uri: String::from("https://example.com/watch?v=1&t=2"),
+                    title: String::from("Rock \"n\" Roll, Part 1"),
+                    len: Duration::from_secs(185),
+                },
+                SavedTrack {
+                    uri: String::from("https://example.com/live"),
+                    title: String::from("Radio"),
+                    len: Duration::default(),
+                },
+            ],
+        }
+    }
+
+    fn round_trip(format: Format) -> Imported {
+        let written = write(format, &playlist()).unwrap();
+        parse(format, &written).unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        for format in [Format::M3u8, Format::Xspf, Format::Json].iter() {
+            let imported = round_trip(*format);
+            assert!(imported.problems.is_empty(), "{:?}", format);
+            assert_eq!(imported.playlist.name, playlist().name, "{:?}", format);
+            assert_eq!(imported.playlist.tracks, playlist().tracks, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn m3u8_reports_lines() {
+        let content = "#EXTM3U\n\
+                       #EXTINF:abc,Broken\n\
+                       https://example.com/a\n\
+                       C:\\Music\\b.mp3\n\
+                       file:///music/c.mp3\n\
+                       #EXTINF:-1 tvg-id=\"x\",Stream\n\
+                       https://example.com/d\n";
+        let imported = parse(Format::M3u8, content).unwrap();
+        let titles: Vec<_> = imported.playlist.tracks.iter().map(|t| &t.title).collect();
+        assert_eq!(titles, ["https://example.com/a", "Stream"]);
+        assert_eq!(imported.problems.len(), 3);
+        assert!(imported.problems[0].starts_with("Line 2: Invalid #EXTINF"));
+        assert!(imported.problems[1].starts_with("Line 4: "));
+        assert!(imported.problems[2].starts_with("Line 5: Unsupported location"));
+    }
+
+    #[test]
+    fn xspf_reads_prefixed_tags_cdata_and_entities() {
+        let content = "<?xml version=\"1.0\"?>\n\
+                       <?xml-stylesheet href=\"playlist.xsl\"?>\n\
+                       <x:playlist version=\"1\" xmlns:x=\"http://xspf.org/ns/0/\">\n\
+                       <x:title><![CDATA[Tom & Jerry]]></x:title>\n\
+                       <x:trackList>\n\
+                       <!-- <x:track><x:location>https://example.com/c</x:location></x:track> -->\n\
+                       <x:track id=\"1\">\n\
+                       <x:location xml:base=\"x\"> https://example.com/a?x=1&amp;y=2 </x:location>\n\
+                       <x:title>Caf&#233; <?pi?>&#x263A;</x:title>\n\
+                       <x:duration>1000</x:duration>\n\
+                       </x:track>\n\
+                       </x:trackList>\n\
+                       </x:playlist>\n";
+        let imported = parse(Format::Xspf, content).unwrap();
+        assert!(imported.problems.is_empty(), "{:?}", imported.problems);
+        assert_eq!(imported.playlist.name, "Tom & Jerry");
+        assert_eq!(
+            imported.playlist.tracks,
+            [SavedTrack {
+                uri: String::from("https://example.com/a?x=1&y=2"),
+                title: String::from("Caf\u{e9} \u{263a}"),
+                len: Duration::from_secs(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn xspf_reports_tracks() {
+        let content = "<playlist><trackList>\n\
+                       <track/>\n\
+                       <track><title>No location</title></track>\n\
+                       <track><location>ftp://example.com/a</location></track>\n\
+                       <track><location>https://example.com/b</location><duration>x</duration></track>\n\
+                       </trackList></playlist>";
+        let imported = parse(Format::Xspf, content).unwrap();
+        assert_eq!(imported.playlist.tracks.len(), 1);
+        assert_eq!(imported.playlist.tracks[0].len, Duration::default());
+        assert_eq!(
+            imported.problems,
+            [
+                "Line 2: Track without a location",
+                "Line 3: Track without a location",
+                "Line 4: Unsupported location: ftp://example.com/a",
+                "Line 5: Invalid duration, imported without a length",
+            ]
+        );
+    }
+
+    #[test]
+    fn xspf_rejects_invalid_xml() {
+        let unclosed = "<playlist><trackList>\n\
+                        <track><location>https://example.com/a</location>\n\
+                        </trackList></playlist>";
+        assert!(parse(Format::Xspf, unclosed).is_err());
+        assert!(parse(Format::Xspf, "<html></html>").is_err());
+    }
+
+    #[test]
+    fn json_reports_tracks() {
+        let content = r#"{"version": 1, "name": "List", "tracks": [
+            {"uri": "https://example.com/a", "length": 1000},
+            {"title": "No uri"},
+            {"uri": "not a url"}
+        ]}"#;
+        let imported = parse(Format::Json, content).unwrap();
+        assert_eq!(imported.playlist.tracks.len(), 1);
+        assert_eq!(imported.playlist.tracks[0].title, "https://example.com/a");
+        assert_eq!(imported.problems.len(), 2);
+        assert!(imported.problems[0].starts_with("Track 2: missing field `uri`"));
+        assert_eq!(imported.problems[1], "Track 3: Invalid url: not a url");
+    }
+
+    #[test]
+    fn json_rejects_newer_versions() {
+        let content = format!(r#"{{"version": {}, "name": "List"}}"#, JSON_VERSION + 1);
+        assert!(parse(Format::Json, &content).is_err());
+        assert!(parse(Format::Json, "[]").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::startup::StartupError;
+use crate::theme::Theme;
+use crate::Message;
+use iced::{Align, Button, Column, Container, Element, Length, Row, Scrollable, Text};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub enum RecoveryMessage {
+    ResetConfig,
+    OpenConfigFolder,
+    OpenCrashReport(PathBuf),
+    CopyCrashReport(PathBuf),
+    Continue,
+}
+
+/// Shown instead of the player when something went wrong during startup or the last run crashed
+#[derive(Debug)]
+pub struct RecoveryView {
+    errors: Vec<StartupError>,
+    crash_report: Option<PathBuf>,
+    status: Option<String>,
+
+    scroll: iced::scrollable::State,
+    reset_button_state: iced::button::State,
+    open_button_state: iced::button::State,
+    open_report_button_state: iced::button::State,
+    copy_report_button_state: iced::button::State,
+    continue_button_state: iced::button::State,
+}
+
+impl RecoveryView {
+    pub fn new(errors: Vec<StartupError>, crash_report: Option<PathBuf>) -> Self {
+        RecoveryView {
+            errors,
+            crash_report,
+            status: None,
+            scroll: Default::default(),
+            reset_button_state: Default::default(),
+            open_button_state: Default::default(),
+            open_report_button_state: Default::default(),
+            copy_report_button_state: Default::default(),
+            continue_button_state: Default::default(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.errors.is_empty() || self.crash_report.is_some()
+    }
+
+    pub fn has_config_error(&self) -> bool {
+        self.errors.iter().any(StartupError::is_config_error)
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    pub fn dismiss(&mut self) {
+        self.errors.clear();
+        self.crash_report = None;
+        self.status = None;
+    }
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, Message> {
+        let mut errors = Column::new().spacing(5);
+        for e in self.errors.iter() {
+            errors = errors.push(Text::new(e.to_string()).size(16).color(theme.text_color()));
+        }
+
+        let continue_label = match self.has_config_error() {
+            true => "Continue with Defaults",
+            false => "Continue",
+        };
+        let mut buttons = Row::new().spacing(10);
+        if self.has_config_error() {
+            buttons = buttons.push(
+                Button::new(&mut self.reset_button_state, Text::new("Reset Config"))
+                    .on_press(Message::Recovery(RecoveryMessage::ResetConfig))
+                    .style(theme.tab_button_theme()),
+            );
+        }
+        if let Some(report) = self.crash_report.as_ref() {
+            buttons = buttons
+                .push(
+                    Button::new(&mut self.open_report_button_state, Text::new("Open Report"))
+                        .on_press(Message::Recovery(RecoveryMessage::OpenCrashReport(
+                            report.clone(),
+                        )))
+                        .style(theme.tab_button_theme()),
+                )
+                .push(
+                    Button::new(&mut self.copy_report_button_state, Text::new("Copy Report"))
+                        .on_press(Message::Recovery(RecoveryMessage::CopyCrashReport(
+                            report.clone(),
+                        )))
+                        .style(theme.tab_button_theme()),
+                );
+        }
+        if !self.errors.is_empty() {
+            buttons = buttons.push(
+                Button::new(&mut self.open_button_state, Text::new("Open Config Folder"))
+                    .on_press(Message::Recovery(RecoveryMessage::OpenConfigFolder))
+                    .style(theme.tab_button_theme()),
+            );
+        }
+        buttons = buttons.push(
+            Button::new(&mut self.continue_button_state, Text::new(continue_label))
+                .on_press(Message::Recovery(RecoveryMessage::Continue))
+                .style(theme.tab_button_theme()),
+        );
+
+        let title = match self.errors.is_empty() {
+            true => "The companion crashed during the last run",
+            false => "Something went wrong during startup",
+        };
+        if let Some(report) = self.crash_report.as_ref() {
+            errors = errors.push(
+                Text::new(format!("A crash report was written to {:?}", report))
+                    .size(16)
+                    .color(theme.text_color()),
+            );
+        }
+        let mut column = Column::new()
+            .spacing(15)
+            .max_width(800)
+            .push(Text::new(title).size(32).color(theme.text_color()))
+            .push(Scrollable::new(&mut self.scroll).push(errors).max_height(300))
+            .push(buttons);
+        if let Some(status) = self.status.as_ref() {
+            column = column.push(Text::new(status.as_str()).size(16).color(theme.text_color()));
+        }
+
+        Container::new(column)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Align::Center)
+            .align_y(Align::Center)
+            .padding(20)
+            .style(theme.tab_view_container_theme())
+            .into()
+    }
+}
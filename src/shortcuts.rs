@@ -0,0 +1,405 @@
+use crate::theme::Theme;
+use crate::Message;
+use iced::{Align, Button, Column, Container, Element, Length, Row, Scrollable, Text};
+use iced_native::keyboard::{KeyCode, Modifiers};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Seconds skipped by the short and long seek shortcuts
+pub const SHORT_SEEK_SECS: i64 = 5;
+pub const LONG_SEEK_SECS: i64 = 30;
+
+/// Everything that can be bound to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    PlayPause,
+    Next,
+    Previous,
+    Repeat,
+    SeekBack,
+    SeekForward,
+    SeekBackLong,
+    SeekForwardLong,
+    PlaylistTab,
+    HistoryTab,
+    SearchTab,
+    LibraryTab,
+    ActivityTab,
+    LogsTab,
+    SettingsTab,
+    FocusSearch,
+    CheatSheet,
+}
+
+impl Action {
+    pub const ALL: [Action; 17] = [
+        Action::PlayPause,
+        Action::Next,
+        Action::Previous,
+        Action::Repeat,
+        Action::SeekBack,
+        Action::SeekForward,
+        Action::SeekBackLong,
+        Action::SeekForwardLong,
+        Action::PlaylistTab,
+        Action::HistoryTab,
+        Action::SearchTab,
+        Action::LibraryTab,
+        Action::ActivityTab,
+        Action::LogsTab,
+        Action::SettingsTab,
+        Action::FocusSearch,
+        Action::CheatSheet,
+    ];
+
+    /// Binding used if the config does not replace it
+    pub fn default_binding(&self) -> &'static str {
+        match self {
+            Action::PlayPause => "Space",
+            Action::Next => "Ctrl+Right",
+            Action::Previous => "Ctrl+Left",
+            Action::Repeat => "R",
+            Action::SeekBack => "Left",
+            Action::SeekForward => "Right",
+            Action::SeekBackLong => "Shift+Left",
+            Action::SeekForwardLong => "Shift+Right",
+            Action::PlaylistTab => "Ctrl+1",
+            Action::HistoryTab => "Ctrl+2",
+            Action::SearchTab => "Ctrl+3",
+            Action::LibraryTab => "Ctrl+4",
+            Action::ActivityTab => "Ctrl+5",
+            Action::LogsTab => "Ctrl+6",
+            Action::SettingsTab => "Ctrl+7",
+            Action::FocusSearch => "Ctrl+F",
+            Action::CheatSheet => "F1",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Action::PlayPause => String::from("Play / Pause"),
+            Action::Next => String::from("Next track"),
+            Action::Previous => String::from("Previous track"),
+            Action::Repeat => String::from("Switch repeat mode"),
+            Action::SeekBack => format!("Rewind {}s", SHORT_SEEK_SECS),
+            Action::SeekForward => format!("Forward {}s", SHORT_SEEK_SECS),
+            Action::SeekBackLong => format!("Rewind {}s", LONG_SEEK_SECS),
+            Action::SeekForwardLong => format!("Forward {}s", LONG_SEEK_SECS),
+            Action::PlaylistTab => String::from("Show Playlist"),
+            Action::HistoryTab => String::from("Show History"),
+            Action::SearchTab => String::from("Show Search"),
+            Action::LibraryTab => String::from("Show Library"),
+            Action::ActivityTab => String::from("Show Activity"),
+            Action::LogsTab => String::from("Show Logs"),
+            Action::SettingsTab => String::from("Show Settings"),
+            Action::FocusSearch => String::from("Search"),
+            Action::CheatSheet => String::from("Show / Hide this list"),
+        }
+    }
+}
+
+/// Names accepted for keys, besides single letters and digits
+const KEY_NAMES: [(&str, KeyCode); 40] = [
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Minus", KeyCode::Minus),
+    ("Plus", KeyCode::Plus),
+    ("Slash", KeyCode::Slash),
+    ("PlayPause", KeyCode::PlayPause),
+    ("NextTrack", KeyCode::NextTrack),
+    ("PrevTrack", KeyCode::PrevTrack),
+    ("MediaStop", KeyCode::MediaStop),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("NumpadAdd", KeyCode::NumpadAdd),
+    ("NumpadSubtract", KeyCode::NumpadSubtract),
+    ("NumpadEnter", KeyCode::NumpadEnter),
+    ("Grave", KeyCode::Grave),
+];
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+];
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// A key together with the modifiers which have to be held, e.g. `Ctrl+Shift+Right`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl KeyBinding {
+    pub fn matches(&self, key: KeyCode, modifiers: Modifiers) -> bool {
+        self.key == key && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::default();
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        //"Ctrl++" binds the plus key
+        if s.trim().ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("Plus");
+        }
+        let key = parts.pop().filter(|k| !k.is_empty()).ok_or("Missing key")?;
+        for part in parts {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.control = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                "logo" | "super" | "win" | "cmd" => modifiers.logo = true,
+                _ => return Err(format!("Unknown modifier: {}", part)),
+            }
+        }
+        let key = key_from_name(key).ok_or_else(|| format!("Unknown key: {}", key))?;
+        Ok(KeyBinding { key, modifiers })
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.logo {
+            write!(f, "Logo+")?;
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphabetic() {
+            return Some(LETTERS[(c.to_ascii_uppercase() as u8 - b'A') as usize]);
+        }
+        if let Some(d) = c.to_digit(10) {
+            return Some(DIGITS[d as usize]);
+        }
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, k)| *k)
+}
+
+fn key_name(key: KeyCode) -> String {
+    if let Some(i) = LETTERS.iter().position(|k| *k == key) {
+        return ((b'A' + i as u8) as char).to_string();
+    }
+    if let Some(i) = DIGITS.iter().position(|k| *k == key) {
+        return i.to_string();
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(_, k)| *k == key)
+        .map(|(n, _)| n.to_string())
+        .unwrap_or_else(|| format!("{:?}", key))
+}
+
+#[derive(Debug, Clone)]
+pub enum ShortcutMessage {
+    KeyPressed(KeyCode, Modifiers),
+    CloseCheatSheet(),
+}
+
+/// Maps key presses to actions and shows the list of bindings on demand
+#[derive(Debug)]
+pub struct Shortcuts {
+    bindings: Vec<(Action, Option<KeyBinding>)>,
+    cheat_sheet: bool,
+
+    scroll: iced::scrollable::State,
+    close_button_state: iced::button::State,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        Shortcuts {
+            bindings: Action::ALL
+                .iter()
+                .map(|a| (*a, a.default_binding().parse().ok()))
+                .collect(),
+            cheat_sheet: false,
+            scroll: Default::default(),
+            close_button_state: Default::default(),
+        }
+    }
+
+    /// Replaces the bindings by the defaults overlaid with the configured ones.
+    /// An empty binding unbinds the action, invalid ones keep the default.
+    pub fn set_bindings(&mut self, configured: &BTreeMap<Action, String>) {
+        self.bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                let default = action.default_binding().parse().ok();
+                let binding = match configured.get(action).map(|b| b.trim()) {
+                    None => default,
+                    Some("") => None,
+                    Some(b) => match b.parse() {
+                        Ok(binding) => Some(binding),
+                        Err(e) => {
+                            warn!("Invalid shortcut for {:?}: {}", action, e);
+                            default
+                        }
+                    },
+                };
+                (*action, binding)
+            })
+            .collect();
+    }
+
+    pub fn is_cheat_sheet_shown(&self) -> bool {
+        self.cheat_sheet
+    }
+
+    /// Handles the message, returning the action to run
+    pub fn update(&mut self, message: ShortcutMessage) -> Option<Action> {
+        match message {
+            ShortcutMessage::KeyPressed(key, modifiers) => {
+                if self.cheat_sheet && key == KeyCode::Escape && modifiers == Modifiers::default() {
+                    self.cheat_sheet = false;
+                    return None;
+                }
+                let action = self
+                    .bindings
+                    .iter()
+                    .find(|(_, b)| b.map(|b| b.matches(key, modifiers)).unwrap_or(false))
+                    .map(|(a, _)| *a)?;
+                if action == Action::CheatSheet {
+                    self.cheat_sheet = !self.cheat_sheet;
+                }
+                Some(action)
+            }
+            ShortcutMessage::CloseCheatSheet() => {
+                self.cheat_sheet = false;
+                None
+            }
+        }
+    }
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, Message> {
+        let mut list = Scrollable::new(&mut self.scroll)
+            .spacing(5)
+            .width(Length::Fill)
+            .height(Length::Fill);
+        for (action, binding) in self.bindings.iter() {
+            let binding = binding
+                .map(|b| b.to_string())
+                .unwrap_or_else(|| String::from("-"));
+            list = list.push(
+                Row::new()
+                    .push(
+                        Text::new(binding)
+                            .size(16)
+                            .color(theme.text_color())
+                            .width(Length::Units(150)),
+                    )
+                    .push(
+                        Text::new(action.description())
+                            .size(16)
+                            .color(theme.text_color())
+                            .width(Length::Fill),
+                    ),
+            );
+        }
+
+        let header = Row::new()
+            .align_items(Align::Center)
+            .push(
+                Text::new("Keyboard Shortcuts")
+                    .size(crate::tabs::HEADER_SIZE)
+                    .color(theme.text_color())
+                    .width(Length::Fill),
+            )
+            .push(
+                Button::new(&mut self.close_button_state, Text::new("Close").size(16))
+                    .on_press(Message::Shortcut(ShortcutMessage::CloseCheatSheet()))
+                    .style(theme.tab_button_theme()),
+            );
+
+        Container::new(Column::new().spacing(10).push(header).push(list))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(crate::tabs::TAB_PADDING)
+            .style(theme.tab_view_container_theme())
+            .into()
+    }
+}
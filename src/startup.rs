@@ -0,0 +1,183 @@
+use crate::cli::Opt;
+use crate::config::Config;
+use directories::ProjectDirs;
+use iced::window::Icon;
+use image::ImageFormat;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub enum StartupError {
+    NoProjectDir,
+    CreateConfigDir(PathBuf, Arc<std::io::Error>),
+    CreateDataDir(PathBuf, Arc<std::io::Error>),
+    CreateLogFile(PathBuf, Arc<std::io::Error>),
+    LogConfig(String),
+    InitLog(String),
+    OpenConfig(PathBuf, Arc<std::io::Error>),
+    ParseConfig(PathBuf, Arc<serde_yaml::Error>),
+    WriteConfig(PathBuf, String),
+    LoadIcon(String),
+    /// The `--profile` given on the command line, with the existing profiles
+    UnknownProfile(String),
+}
+
+impl StartupError {
+    /// Whether the error concerns the config file, so resetting it could help
+    pub fn is_config_error(&self) -> bool {
+        matches!(
+            self,
+            StartupError::CreateConfigDir(..)
+                | StartupError::OpenConfig(..)
+                | StartupError::ParseConfig(..)
+                | StartupError::WriteConfig(..)
+        )
+    }
+}
+
+impl Display for StartupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupError::NoProjectDir => write!(f, "Could not get Project Dir"),
+            StartupError::CreateConfigDir(p, e) => {
+                write!(f, "Could not create config path {:?}: {}", p, e)
+            }
+            StartupError::CreateDataDir(p, e) => {
+                write!(f, "Could not create data path {:?}: {}", p, e)
+            }
+            StartupError::CreateLogFile(p, e) => {
+                write!(f, "Could not create log file {:?}: {}", p, e)
+            }
+            StartupError::LogConfig(e) => write!(f, "Could not create log config: {}", e),
+            StartupError::InitLog(e) => write!(f, "Could not init log: {}", e),
+            StartupError::OpenConfig(p, e) => write!(f, "Could not open config {:?}: {}", p, e),
+            StartupError::ParseConfig(p, e) => {
+                write!(f, "Error parsing config {:?}: {}", p, e)
+            }
+            StartupError::WriteConfig(p, e) => {
+                write!(f, "Could not write config {:?}: {}", p, e)
+            }
+            StartupError::LoadIcon(e) => write!(f, "Could not load icon: {}", e),
+            StartupError::UnknownProfile(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Everything gathered before the window opens
+#[derive(Debug)]
+pub struct Startup {
+    pub config: Config,
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub errors: Vec<StartupError>,
+}
+
+/// Creates the directories and loads the config.
+/// Failures are collected instead of aborting, falling back to defaults where needed.
+pub fn load(opt: &Opt) -> Startup {
+    let mut errors = Vec::new();
+    let proj_dir = ProjectDirs::from("de", "Autumnal", "Reciprocity Companion");
+    if proj_dir.is_none() && (opt.config.is_none() || opt.data_dir.is_none()) {
+        errors.push(StartupError::NoProjectDir);
+    }
+
+    let config_path = opt.config.clone().unwrap_or_else(|| {
+        proj_dir
+            .as_ref()
+            .map(|d| d.config_dir().to_path_buf())
+            .unwrap_or_default()
+            .join("config.yml")
+    });
+    let data_dir = opt.data_dir.clone().unwrap_or_else(|| {
+        proj_dir
+            .as_ref()
+            .map(|d| d.data_dir().to_path_buf())
+            .unwrap_or_default()
+    });
+
+    if let Some(config_dir) = config_path.parent() {
+        if let Err(e) = std::fs::create_dir_all(config_dir) {
+            errors.push(StartupError::CreateConfigDir(
+                config_dir.to_path_buf(),
+                Arc::new(e),
+            ));
+        }
+    }
+    if let Err(e) = std::fs::create_dir_all(&data_dir) {
+        errors.push(StartupError::CreateDataDir(data_dir.clone(), Arc::new(e)));
+    }
+
+    let config = match load_config(&config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            errors.push(e);
+            Config::default()
+        }
+    };
+
+    Startup {
+        config,
+        config_path,
+        data_dir,
+        errors,
+    }
+}
+
+/// Starts logging into the data dir with the levels and rotation of the config
+pub fn init_log(data_dir: &Path, config: &Config) -> Result<(), StartupError> {
+    crate::log::init(data_dir, config.log_level, &config.logging)
+}
+
+/// Loads the config, creating a default one if none exists yet
+pub fn load_config(path: &Path) -> Result<Config, StartupError> {
+    if path.exists() {
+        let file = File::open(path)
+            .map_err(|e| StartupError::OpenConfig(path.to_path_buf(), Arc::new(e)))?;
+        let mut config: Config = serde_yaml::from_reader(file)
+            .map_err(|e| StartupError::ParseConfig(path.to_path_buf(), Arc::new(e)))?;
+        config.normalize();
+        Ok(config)
+    } else {
+        let config = Config::default();
+        write_config(&config, path)?;
+        Ok(config)
+    }
+}
+
+/// Replaces the config at the path with the default config.
+/// The previous file is kept next to it with a `.bak` extension.
+pub fn reset_config(path: &Path) -> Result<Config, StartupError> {
+    if path.exists() {
+        std::fs::rename(path, path.with_extension("yml.bak"))
+            .map_err(|e| StartupError::WriteConfig(path.to_path_buf(), e.to_string()))?;
+    }
+    let config = Config::default();
+    write_config(&config, path)?;
+    Ok(config)
+}
+
+fn write_config(config: &Config, path: &Path) -> Result<(), StartupError> {
+    let bytes = serde_yaml::to_vec(config)
+        .map_err(|e| StartupError::WriteConfig(path.to_path_buf(), e.to_string()))?;
+    File::create(path)
+        .and_then(|mut f| f.write_all(bytes.as_slice()))
+        .map_err(|e| StartupError::WriteConfig(path.to_path_buf(), e.to_string()))
+}
+
+pub fn load_icon() -> Result<Icon, StartupError> {
+    let icon = image::load_from_memory_with_format(
+        include_bytes!("./icons/google/twotone_settings_remote_white_48dp.png"),
+        ImageFormat::Png,
+    )
+    .map_err(|e| StartupError::LoadIcon(e.to_string()))?;
+    let icon = icon.to_rgba8();
+    Icon::from_rgba(
+        icon.pixels().map(|rgba| rgba.0.iter()).flatten().cloned().collect(),
+        96,
+        96,
+    )
+    .map_err(|e| StartupError::LoadIcon(format!("{:?}", e)))
+}
@@ -1 +1,104 @@
+use crate::util::youtube::Video;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
+const UI_STATE_FILE: &str = "ui_state.yml";
+
+/// UI state which is restored on the next start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiState {
+    #[serde(default = "default_window_size")]
+    pub window_size: (u32, u32),
+    #[serde(default)]
+    pub tab: TabKey,
+    #[serde(default)]
+    pub search_query: String,
+    #[serde(default)]
+    pub search_results: Vec<Video>,
+    #[serde(default)]
+    pub scroll: ScrollOffsets,
+}
+
+/// Tab stored by name, as its index changes when tabs are added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabKey {
+    Playlist,
+    History,
+    Search,
+    Library,
+    Activity,
+    Logs,
+    Settings,
+}
+
+impl Default for TabKey {
+    fn default() -> Self {
+        TabKey::Playlist
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrollOffsets {
+    #[serde(default)]
+    pub playlist: f32,
+    #[serde(default)]
+    pub history: f32,
+    #[serde(default)]
+    pub search: f32,
+    #[serde(default)]
+    pub settings: f32,
+}
+
+fn default_window_size() -> (u32, u32) {
+    (1280, 750)
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        UiState {
+            window_size: default_window_size(),
+            tab: TabKey::default(),
+            search_query: String::new(),
+            search_results: Vec::new(),
+            scroll: Default::default(),
+        }
+    }
+}
+
+impl UiState {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(UI_STATE_FILE)
+    }
+
+    /// Loads the state of the last session, falling back to the default state
+    pub fn load(data_dir: &Path) -> Self {
+        let path = UiState::path(data_dir);
+        if !path.exists() {
+            return UiState::default();
+        }
+        File::open(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|f| serde_yaml::from_reader(f).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                log::warn!("Could not load UI state {:?}: {}", path, e);
+                UiState::default()
+            })
+    }
+
+    pub fn save(&self, data_dir: &Path) {
+        let path = UiState::path(data_dir);
+        let res = serde_yaml::to_vec(self)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                File::create(&path)
+                    .and_then(|mut f| f.write_all(bytes.as_slice()))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = res {
+            log::error!("Could not save UI state {:?}: {}", path, e);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use crate::icons::Icon;
+use crate::tabs::Tab;
+use crate::theme::Theme;
+use crate::util::{dialog, duration_fmt};
+use crate::Message;
+use chrono::{DateTime, Local};
+use iced::{
+    Button, Column, Command, Element, Length, Radio, Row, Scrollable, Space, Text, TextInput,
+};
+use reciprocity_communication::messages::{PlayerControl, PlayerControlResult};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Entries kept for the session, older ones are dropped
+const MAX_ENTRIES: usize = 5000;
+/// Rendering thousands of lines makes the layout sluggish
+const MAX_SHOWN_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Playback,
+    Skip,
+    Seek,
+    Enqueue,
+    Mode,
+    Other,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 6] = [
+        ActionKind::Playback,
+        ActionKind::Skip,
+        ActionKind::Seek,
+        ActionKind::Enqueue,
+        ActionKind::Mode,
+        ActionKind::Other,
+    ];
+}
+
+/// One control request of any user, as reported by the bot
+#[derive(Debug, Clone)]
+pub struct ActivityEntry {
+    pub time: DateTime<Local>,
+    pub user: String,
+    pub kind: ActionKind,
+    pub action: String,
+    pub success: bool,
+}
+
+impl ActivityEntry {
+    pub fn from_result(res: &PlayerControlResult) -> Self {
+        let (kind, action) = describe(&res.control);
+        ActivityEntry {
+            time: Local::now(),
+            user: res.user.username.clone(),
+            kind,
+            action,
+            success: res.result.is_ok(),
+        }
+    }
+
+    fn csv_line(&self) -> String {
+        [
+            self.time.to_rfc3339(),
+            self.user.clone(),
+            format!("{:?}", self.kind),
+            self.action.clone(),
+            self.success.to_string(),
+        ]
+        .iter()
+        .map(|f| format!("\"{}\"", f.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+fn describe(control: &PlayerControl) -> (ActionKind, String) {
+    match control {
+        PlayerControl::Pause() => (ActionKind::Playback, String::from("Paused")),
+        PlayerControl::Resume() => (ActionKind::Playback, String::from("Resumed")),
+        PlayerControl::Skip(n) => (ActionKind::Skip, format!("Skipped {}", n)),
+        PlayerControl::BackSkip(n) => (ActionKind::Skip, format!("Skipped back {}", n)),
+        PlayerControl::SetTime(t) => (ActionKind::Seek, format!("Seeked to {}", duration_fmt(t))),
+        PlayerControl::Enqueue(url) => (ActionKind::Enqueue, format!("Enqueued {}", url)),
+        PlayerControl::PlayMode(mode) => (ActionKind::Mode, format!("Set mode {:?}", mode)),
+        #[allow(unreachable_patterns)]
+        other => (ActionKind::Other, format!("{:?}", other)),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ActivityMessage {
+    Reported(PlayerControlResult),
+    UserFilterChanged(String),
+    KindSelected(Option<ActionKind>),
+    Export(),
+    Exported(Result<Option<PathBuf>, String>),
+}
+
+#[derive(Debug)]
+pub struct ActivityTab {
+    entries: VecDeque<ActivityEntry>,
+    user_filter: String,
+    kind_filter: Option<ActionKind>,
+    status: Option<String>,
+
+    scroll: iced::scrollable::State,
+    user_input: iced::text_input::State,
+    export_button_state: iced::button::State,
+}
+
+impl ActivityTab {
+    pub fn new() -> Self {
+        ActivityTab {
+            entries: VecDeque::new(),
+            user_filter: String::new(),
+            kind_filter: None,
+            status: None,
+            scroll: Default::default(),
+            user_input: Default::default(),
+            export_button_state: Default::default(),
+        }
+    }
+
+    pub fn update(&mut self, message: ActivityMessage) -> Command<Message> {
+        match message {
+            ActivityMessage::Reported(res) => {
+                if self.entries.len() >= MAX_ENTRIES {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(ActivityEntry::from_result(&res));
+            }
+            ActivityMessage::UserFilterChanged(user) => self.user_filter = user,
+            ActivityMessage::KindSelected(kind) => self.kind_filter = kind,
+            ActivityMessage::Export() => {
+                let csv = std::iter::once(String::from("time,user,kind,action,success"))
+                    .chain(self.filtered().map(ActivityEntry::csv_line))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Command::perform(
+                    async move {
+                        let path = dialog::save_path(
+                            "CSV",
+                            &["csv"],
+                            String::from("reciprocity_activity.csv"),
+                        )
+                        .await;
+                        match path {
+                            Some(path) => File::create(&path)
+                                .and_then(|mut f| f.write_all(csv.as_bytes()))
+                                .map(|_| Some(path))
+                                .map_err(|e| e.to_string()),
+                            None => Ok(None),
+                        }
+                    },
+                    |res| Message::Activity(ActivityMessage::Exported(res)),
+                );
+            }
+            ActivityMessage::Exported(res) => {
+                self.status = match res {
+                    Ok(Some(path)) => Some(format!("Exported to {:?}", path)),
+                    Ok(None) => None,
+                    Err(e) => Some(format!("Export failed: {}", e)),
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    fn filtered(&self) -> impl Iterator<Item = &ActivityEntry> {
+        let user = self.user_filter.to_lowercase();
+        let kind = self.kind_filter;
+        self.entries.iter().filter(move |e| {
+            kind.map(|k| k == e.kind).unwrap_or(true)
+                && (user.is_empty() || e.user.to_lowercase().contains(&user))
+        })
+    }
+}
+
+impl Tab for ActivityTab {
+    type Message = Message;
+
+    fn title(&self) -> String {
+        String::from("Activity")
+    }
+
+    fn tab_label(&self) -> (Option<Icon>, String) {
+        (Icon::List.into(), "Activity".to_string())
+    }
+
+    fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message> {
+        let mut kinds = Row::new().spacing(10).push(
+            Radio::new(None, "All", Some(self.kind_filter), |k| {
+                Message::Activity(ActivityMessage::KindSelected(k))
+            })
+            .style(theme.radio_button_theme()),
+        );
+        for kind in ActionKind::ALL.iter() {
+            kinds = kinds.push(
+                Radio::new(
+                    Some(*kind),
+                    &format!("{:?}", kind),
+                    Some(self.kind_filter),
+                    |k| Message::Activity(ActivityMessage::KindSelected(k)),
+                )
+                .style(theme.radio_button_theme()),
+            );
+        }
+
+        let mut entries = Column::new().width(Length::Fill);
+        for entry in self.filtered().rev().take(MAX_SHOWN_ENTRIES) {
+            let color = match entry.success {
+                true => theme.text_color(),
+                false => theme.error_color(),
+            };
+            let row = Row::new()
+                .push(
+                    Text::new(entry.time.format("%H:%M:%S").to_string())
+                        .size(16)
+                        .color(color)
+                        .width(Length::Units(90)),
+                )
+                .push(
+                    Text::new(entry.user.as_str())
+                        .size(16)
+                        .color(color)
+                        .width(Length::Units(160)),
+                )
+                .push(
+                    Text::new(entry.action.as_str())
+                        .size(16)
+                        .color(color)
+                        .width(Length::Fill),
+                )
+                .push(Space::new(Length::Units(15), Length::Shrink));
+            entries = entries.push(row);
+        }
+
+        let user_input = TextInput::new(&mut self.user_input, "User...", &self.user_filter, |u| {
+            Message::Activity(ActivityMessage::UserFilterChanged(u))
+        })
+        .padding(5)
+        .style(theme.search_input_theme());
+        let mut actions = Row::new().spacing(10).push(user_input).push(
+            Button::new(&mut self.export_button_state, Text::new("Export"))
+                .on_press(Message::Activity(ActivityMessage::Export()))
+                .style(theme.tab_button_theme()),
+        );
+        if let Some(status) = self.status.as_ref() {
+            actions = actions.push(Text::new(status.as_str()).size(14).color(theme.text_color()));
+        }
+
+        Column::new()
+            .spacing(10)
+            .push(kinds)
+            .push(actions)
+            .push(
+                Scrollable::new(&mut self.scroll)
+                    .push(entries)
+                    .height(Length::Fill),
+            )
+            .into()
+    }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
+    }
+}
@@ -1,11 +1,18 @@
 use crate::connection::Connection;
+use crate::drag_state::{DragItem, DragMessage};
+use crate::error::{self, Error};
 use crate::icons::Icon;
+use crate::library::{SavedPlaylist, SavedTrack};
+use crate::playlist_file;
 use crate::tabs::Tab;
 use crate::theme::Theme;
-use crate::util::duration_fmt;
-use crate::{Message, MAX_DOUBLE_CLICK_INTERVAL};
+use crate::toast::{self, Toast};
+use crate::util::drag::DragArea;
+use crate::util::track_len_fmt;
+use crate::{double_click_interval, Message, PLAYLIST_TAB};
 use iced::{
-    Button, Column, Command, Element, HorizontalAlignment, Length, Row, Scrollable, Space, Text,
+    Align, Button, Column, Command, Element, HorizontalAlignment, Length, Row, Scrollable, Space,
+    Text,
 };
 use reciprocity_communication::messages::{PlayerControl, PlayerState, Track};
 use std::time::Instant;
@@ -15,6 +22,7 @@ use reqwest::Url;
 pub enum HistoryMessage {
     PlayerStateChanged(Option<PlayerState>),
     SongClicked(Track),
+    Export(),
 }
 
 #[derive(Debug)]
@@ -23,6 +31,8 @@ pub struct HistoryTab {
     scroll: iced::scrollable::State,
     last_click: (Track, Instant),
     btn_states: Vec<iced::button::State>,
+    save_btn_states: Vec<iced::button::State>,
+    export_btn_state: iced::button::State,
 }
 
 impl HistoryTab {
@@ -38,6 +48,8 @@ impl HistoryTab {
                 uri: "".to_string()
             }, Instant::now()),
             btn_states: Vec::new(),
+            save_btn_states: Vec::new(),
+            export_btn_state: Default::default(),
         }
     }
 
@@ -52,16 +64,33 @@ impl HistoryTab {
             }
             HistoryMessage::SongClicked(track) => {
                 if self.last_click.0.eq(&track)
-                    && self.last_click.1.elapsed() <= MAX_DOUBLE_CLICK_INTERVAL
+                    && self.last_click.1.elapsed() <= double_click_interval()
                 {
                     self.last_click = (track, Instant::now());
                     if let Some(con) = con {
-                        return con.control_request(PlayerControl::Enqueue(Url::parse(self.last_click.0.uri.as_str()).unwrap()));
+                        return match Url::parse(self.last_click.0.uri.as_str()) {
+                            Ok(url) => {
+                                let added =
+                                    Toast::success(format!("Added: {}", self.last_click.0.title))
+                                        .on_click(Message::TabSelected(PLAYLIST_TAB));
+                                Command::batch(vec![
+                                    con.control_request(PlayerControl::Enqueue(url)),
+                                    toast::show(added),
+                                ])
+                            }
+                            Err(_) => error::report(Error::InvalidUrl(self.last_click.0.uri.clone())),
+                        };
                     }
                 } else {
                     self.last_click = (track, Instant::now());
                 }
             }
+            HistoryMessage::Export() => {
+                return playlist_file::export(SavedPlaylist {
+                    name: String::from("History"),
+                    tracks: self.history.iter().map(SavedTrack::from).collect(),
+                });
+            }
         }
 
         Command::none()
@@ -82,14 +111,28 @@ impl Tab for HistoryTab {
     fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message> {
         let mut column = Column::new().width(Length::Fill);
 
+        if !self.history.is_empty() {
+            let export_btn = Button::new(&mut self.export_btn_state, Text::new("Export"))
+                .on_press(Message::History(HistoryMessage::Export()))
+                .style(theme.tab_button_theme());
+            column = column.push(
+                Row::new()
+                    .push(Space::new(Length::Fill, Length::Shrink))
+                    .push(export_btn)
+                    .push(Space::new(Length::Units(15), Length::Shrink)),
+            );
+        }
+
         while self.btn_states.len() <= self.history.len() {
             self.btn_states.push(Default::default());
+            self.save_btn_states.push(Default::default());
         }
 
-        for (i, (track, btn_state)) in self
+        for (i, ((track, btn_state), save_btn_state)) in self
             .history
             .iter()
             .zip(self.btn_states.iter_mut())
+            .zip(self.save_btn_states.iter_mut())
             .enumerate()
         {
             let i = i + 1;
@@ -102,14 +145,27 @@ impl Tab for HistoryTab {
                         .horizontal_alignment(HorizontalAlignment::Left),
                 )
                 .push(Space::new(Length::Units(10), Length::Shrink))
-                .push(Text::new(duration_fmt(&track.len)))
+                .push(Text::new(track_len_fmt(track)))
                 .push(Space::new(Length::Units(15), Length::Shrink))
                 .width(Length::Fill);
             let btn = Button::new(btn_state, row)
                 .on_press(Message::History(HistoryMessage::SongClicked(track.clone())))
                 .style(theme.tab_button_theme());
+            let btn = DragArea::new(btn).on_press(Message::Drag(DragMessage::Pressed(
+                DragItem::Track(track.clone()),
+            )));
+            let save_btn = Button::new(save_btn_state, Icon::PlaylistAdd.get_svg(theme))
+                .on_press(Message::AddToPlaylist(track.clone()))
+                .style(theme.tab_button_theme())
+                .width(Length::Units(30));
 
-            column = column.push(btn);
+            column = column.push(
+                Row::new()
+                    .push(btn)
+                    .push(save_btn)
+                    .push(Space::new(Length::Units(15), Length::Shrink))
+                    .align_items(Align::Center),
+            );
         }
 
         Scrollable::new(&mut self.scroll)
@@ -117,4 +173,8 @@ impl Tab for HistoryTab {
             .height(Length::Fill)
             .into()
     }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
+    }
 }
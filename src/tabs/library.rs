@@ -0,0 +1,785 @@
+use crate::connection::Connection;
+use crate::drag_state::{DragItem, DragMessage};
+use crate::error::{self, Error};
+use crate::icons::Icon;
+use crate::library::{Library, SavedPlaylist, SavedTrack};
+use crate::playlist_file::{self, Imported};
+use crate::tabs::{Tab, DROP_INDICATOR_HEIGHT, ROW_HEIGHT};
+use crate::theme::Theme;
+use crate::toast::{self, Toast};
+use crate::util::drag::DragArea;
+use crate::util::{duration_fmt, scroll_offset, set_scroll_offset, track_len_fmt};
+use crate::{Message, PLAYLIST_TAB};
+use iced::{
+    Align, Button, Column, Command, Container, Element, HorizontalAlignment, Length, Row,
+    Scrollable, Space, Text, TextInput,
+};
+use log::warn;
+use rand::seq::SliceRandom;
+use reciprocity_communication::messages::{PlayerControl, Track};
+use reqwest::Url;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Time between two tracks sent to the bot while enqueuing a whole playlist
+pub const ENQUEUE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub enum LibraryMessage {
+    NameChanged(String),
+    /// Creates a playlist with the entered name, or renames the open one
+    NameSubmitted(),
+    Opened(Option<usize>),
+    /// Moves the playlist from the first index to the second
+    MovePlaylist(usize, usize),
+    /// Moves the track of the open playlist from the first index to the second
+    MoveTrack(usize, usize),
+    RemoveTrack(usize),
+    /// Deletes the open playlist on the second press
+    Delete(),
+    /// Enqueues the open playlist, shuffled if true
+    Enqueue(bool),
+    EnqueueNext(),
+    CancelEnqueue(),
+    /// Exports the open playlist
+    Export(),
+    Import(),
+    Imported(Result<Option<Imported>, String>),
+    DismissProblems(),
+    Save(),
+    /// Asks for the playlist to add the track to, returning to the tab with the index after
+    Pick(Track, usize),
+    /// Adds the track waiting to be picked to the playlist
+    Picked(usize),
+    CancelPick(),
+}
+
+/// Playlist which is sent to the bot one track after another
+#[derive(Debug)]
+struct Enqueuing {
+    name: String,
+    urls: VecDeque<Url>,
+    total: usize,
+}
+
+#[derive(Debug)]
+pub struct LibraryTab {
+    library: Library,
+    data_dir: PathBuf,
+    /// Index of the playlist shown, all playlists are listed if none
+    open: Option<usize>,
+    name_value: String,
+    confirm_delete: bool,
+    enqueuing: Option<Enqueuing>,
+    /// Name of the last imported playlist and the entries which were skipped
+    import_problems: Option<(String, Vec<String>)>,
+    /// Track to add to the playlist clicked next, with the tab it came from
+    picking: Option<(Track, usize)>,
+    dragging: bool,
+    /// Index the dragged track would be dropped at in the open playlist
+    drop_target: Option<usize>,
+    scroll: iced::scrollable::State,
+    name_input: iced::text_input::State,
+    name_btn_state: iced::button::State,
+    back_btn_state: iced::button::State,
+    enqueue_btn_state: iced::button::State,
+    shuffle_btn_state: iced::button::State,
+    delete_btn_state: iced::button::State,
+    cancel_btn_state: iced::button::State,
+    import_btn_state: iced::button::State,
+    export_btn_state: iced::button::State,
+    dismiss_btn_state: iced::button::State,
+    cancel_pick_btn_state: iced::button::State,
+    /// Buttons of every line, shared by playlists and tracks
+    btn_states: Vec<[iced::button::State; 3]>,
+}
+
+impl LibraryTab {
+    pub fn new(data_dir: PathBuf, library: Library) -> Self {
+        LibraryTab {
+            library,
+            data_dir,
+            open: None,
+            name_value: String::new(),
+            confirm_delete: false,
+            enqueuing: None,
+            import_problems: None,
+            picking: None,
+            dragging: false,
+            drop_target: None,
+            scroll: Default::default(),
+            name_input: Default::default(),
+            name_btn_state: Default::default(),
+            back_btn_state: Default::default(),
+            enqueue_btn_state: Default::default(),
+            shuffle_btn_state: Default::default(),
+            delete_btn_state: Default::default(),
+            cancel_btn_state: Default::default(),
+            import_btn_state: Default::default(),
+            export_btn_state: Default::default(),
+            dismiss_btn_state: Default::default(),
+            cancel_pick_btn_state: Default::default(),
+            btn_states: Vec::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        con: &Option<Connection>,
+        message: LibraryMessage,
+    ) -> Command<Message> {
+        match message {
+            LibraryMessage::NameChanged(name) => self.name_value = name,
+            LibraryMessage::NameSubmitted() => {
+                let name = self.name_value.trim().to_string();
+                if name.is_empty() {
+                    return Command::none();
+                }
+                match self.open {
+                    Some(i) => {
+                        if self.library.playlists[i].name != name {
+                            let name = self.library.unique_name(&name);
+                            self.name_value = name.clone();
+                            self.library.playlists[i].name = name;
+                        }
+                    }
+                    None => {
+                        let name = self.library.unique_name(&name);
+                        self.library.playlists.push(SavedPlaylist {
+                            name,
+                            tracks: Vec::new(),
+                        });
+                        self.name_value.clear();
+                        if self.picking.is_some() {
+                            let created = self.library.playlists.len() - 1;
+                            return self.update(con, LibraryMessage::Picked(created));
+                        }
+                    }
+                }
+                return self.save();
+            }
+            LibraryMessage::Opened(open) => {
+                self.open = open.filter(|i| *i < self.library.playlists.len());
+                self.name_value = self
+                    .open_playlist()
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default();
+                self.confirm_delete = false;
+                set_scroll_offset(&mut self.scroll, 0.0);
+            }
+            LibraryMessage::MovePlaylist(from, to) => {
+                let playlists = &mut self.library.playlists;
+                if from < playlists.len() && to < playlists.len() {
+                    let playlist = playlists.remove(from);
+                    playlists.insert(to, playlist);
+                    return self.save();
+                }
+            }
+            LibraryMessage::MoveTrack(from, to) => {
+                if let Some(playlist) = self.open_playlist_mut() {
+                    if from < playlist.tracks.len() && to < playlist.tracks.len() {
+                        let track = playlist.tracks.remove(from);
+                        playlist.tracks.insert(to, track);
+                        return self.save();
+                    }
+                }
+            }
+            LibraryMessage::RemoveTrack(i) => {
+                if let Some(playlist) = self.open_playlist_mut() {
+                    if i < playlist.tracks.len() {
+                        playlist.tracks.remove(i);
+                        return self.save();
+                    }
+                }
+            }
+            LibraryMessage::Delete() => {
+                let i = match self.open {
+                    Some(i) => i,
+                    None => return Command::none(),
+                };
+                if !self.confirm_delete {
+                    self.confirm_delete = true;
+                    return Command::none();
+                }
+                let playlist = self.library.playlists.remove(i);
+                self.update(con, LibraryMessage::Opened(None));
+                return Command::batch(vec![
+                    self.save(),
+                    toast::show(Toast::info(format!("Deleted {}", playlist.name))),
+                ]);
+            }
+            LibraryMessage::Enqueue(shuffled) => {
+                let playlist = match self.open_playlist() {
+                    Some(playlist) => playlist,
+                    None => return Command::none(),
+                };
+                if con.is_none() {
+                    return toast::show(Toast::warning(String::from("Not connected to the bot")));
+                }
+                let name = playlist.name.clone();
+                let mut tracks = playlist.tracks.clone();
+                if shuffled {
+                    tracks.shuffle(&mut rand::thread_rng());
+                }
+                let urls: VecDeque<Url> = tracks
+                    .iter()
+                    .filter_map(|t| Url::parse(&t.uri).ok())
+                    .collect();
+                let mut commands = Vec::new();
+                if urls.len() < tracks.len() {
+                    commands.push(toast::show(Toast::warning(format!(
+                        "Skipping {} tracks with an invalid url",
+                        tracks.len() - urls.len()
+                    ))));
+                }
+                self.enqueuing = Some(Enqueuing {
+                    name,
+                    total: urls.len(),
+                    urls,
+                });
+                commands.push(self.update(con, LibraryMessage::EnqueueNext()));
+                return Command::batch(commands);
+            }
+            LibraryMessage::EnqueueNext() => {
+                let enqueuing = match self.enqueuing.as_mut() {
+                    Some(enqueuing) => enqueuing,
+                    None => return Command::none(),
+                };
+                let con = match con {
+                    Some(con) => con,
+                    None => {
+                        self.enqueuing = None;
+                        return toast::show(Toast::warning(String::from(
+                            "Stopped enqueuing, the connection was lost",
+                        )));
+                    }
+                };
+                let url = match enqueuing.urls.pop_front() {
+                    Some(url) => url,
+                    None => {
+                        self.enqueuing = None;
+                        return Command::none();
+                    }
+                };
+                let request = con.control_request(PlayerControl::Enqueue(url));
+                if !enqueuing.urls.is_empty() {
+                    return request;
+                }
+                let done = Toast::success(format!(
+                    "Enqueued {} tracks of {}",
+                    enqueuing.total, enqueuing.name
+                ))
+                .on_click(Message::TabSelected(PLAYLIST_TAB));
+                self.enqueuing = None;
+                return Command::batch(vec![request, toast::show(done)]);
+            }
+            LibraryMessage::CancelEnqueue() => {
+                if let Some(enqueuing) = self.enqueuing.take() {
+                    return toast::show(Toast::info(format!(
+                        "Stopped enqueuing {} after {} of {} tracks",
+                        enqueuing.name,
+                        enqueuing.total - enqueuing.urls.len(),
+                        enqueuing.total
+                    )));
+                }
+            }
+            LibraryMessage::Export() => {
+                if let Some(playlist) = self.open_playlist() {
+                    return playlist_file::export(playlist.clone());
+                }
+            }
+            LibraryMessage::Import() => return playlist_file::import(),
+            LibraryMessage::Imported(Ok(Some(Imported {
+                mut playlist,
+                problems,
+            }))) => {
+                playlist.name = self.library.unique_name(&playlist.name);
+                let mut text = format!(
+                    "Imported {} tracks into {}",
+                    playlist.tracks.len(),
+                    playlist.name
+                );
+                if !problems.is_empty() {
+                    text.push_str(&format!(", skipped {} entries", problems.len()));
+                    for problem in problems.iter() {
+                        warn!("Skipped while importing {}: {}", playlist.name, problem);
+                    }
+                }
+                self.import_problems = match problems.is_empty() {
+                    true => None,
+                    false => Some((playlist.name.clone(), problems)),
+                };
+                self.library.playlists.push(playlist);
+                return Command::batch(vec![self.save(), toast::show(Toast::success(text))]);
+            }
+            LibraryMessage::Imported(Ok(None)) => {}
+            LibraryMessage::Imported(Err(e)) => {
+                return toast::show(Toast::warning(format!("Could not import: {}", e)))
+            }
+            LibraryMessage::DismissProblems() => self.import_problems = None,
+            LibraryMessage::Save() => return self.save(),
+            LibraryMessage::Pick(track, origin) => {
+                self.picking = Some((track, origin));
+                self.update(con, LibraryMessage::Opened(None));
+            }
+            LibraryMessage::Picked(i) => {
+                let (track, origin) = match self.picking.take() {
+                    Some(picking) => picking,
+                    None => return Command::none(),
+                };
+                let playlist = match self.library.playlists.get_mut(i) {
+                    Some(playlist) => playlist,
+                    None => return Command::none(),
+                };
+                let added = Toast::success(format!("Saved to {}: {}", playlist.name, track.title));
+                playlist.tracks.push(SavedTrack::from(&track));
+                return Command::batch(vec![
+                    self.save(),
+                    toast::show(added),
+                    Command::perform(futures::future::ready(origin), Message::TabSelected),
+                ]);
+            }
+            LibraryMessage::CancelPick() => self.picking = None,
+        }
+
+        Command::none()
+    }
+
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    /// Replaces all playlists, e.g. by an imported settings bundle
+    pub fn set_library(&mut self, library: Library) -> Command<Message> {
+        self.library = library;
+        self.open = None;
+        self.name_value.clear();
+        self.confirm_delete = false;
+        self.save()
+    }
+
+    /// True while tracks are left to be sent to the bot
+    pub fn is_enqueuing(&self) -> bool {
+        self.enqueuing.is_some()
+    }
+
+    pub fn set_drag(&mut self, dragging: bool, target: Option<usize>) {
+        self.dragging = dragging;
+        self.drop_target = target;
+    }
+
+    /// Scrolls while dragging near the edges, within the list of the given height
+    pub fn scroll_by(&mut self, delta: f32, height: f32) {
+        //An empty playlist still shows a line to drop on
+        let rows = self
+            .open_playlist()
+            .map(|p| p.tracks.len().max(1))
+            .unwrap_or_else(|| self.library.playlists.len());
+        let mut content = (rows * ROW_HEIGHT as usize) as f32;
+        if self.drop_target.is_some() {
+            content += DROP_INDICATOR_HEIGHT as f32;
+        }
+        let max = (content - height).max(0.0);
+        let offset = (scroll_offset(&self.scroll) + delta).max(0.0).min(max);
+        set_scroll_offset(&mut self.scroll, offset);
+    }
+
+    /// Moves a track of the open playlist or adds one at the index
+    pub fn drop(&mut self, item: DragItem, target: usize) -> Command<Message> {
+        let playlist = match self.open_playlist_mut() {
+            Some(playlist) => playlist,
+            None => return Command::none(),
+        };
+        match item {
+            DragItem::Saved(i) => {
+                //The target counts the dragged track itself when it was above it
+                let to = match target > i {
+                    true => target - 1,
+                    false => target,
+                };
+                if to == i || i >= playlist.tracks.len() {
+                    return Command::none();
+                }
+                let track = playlist.tracks.remove(i);
+                playlist.tracks.insert(to, track);
+                self.save()
+            }
+            DragItem::Track(track) => {
+                let added = Toast::success(format!("Saved to {}: {}", playlist.name, track.title));
+                let target = target.min(playlist.tracks.len());
+                playlist.tracks.insert(target, SavedTrack::from(&track));
+                Command::batch(vec![self.save(), toast::show(added)])
+            }
+            //Tracks of the queue are handed over as tracks by the app
+            DragItem::Queued(_) => Command::none(),
+        }
+    }
+
+    /// Track at the index of the open playlist
+    pub fn track(&self, i: usize) -> Option<Track> {
+        self.open_playlist()
+            .and_then(|p| p.tracks.get(i))
+            .map(SavedTrack::track)
+    }
+
+    fn open_playlist(&self) -> Option<&SavedPlaylist> {
+        self.open.and_then(|i| self.library.playlists.get(i))
+    }
+
+    fn open_playlist_mut(&mut self) -> Option<&mut SavedPlaylist> {
+        let open = self.open?;
+        self.library.playlists.get_mut(open)
+    }
+
+    fn save(&self) -> Command<Message> {
+        match self.library.save(&self.data_dir) {
+            Ok(()) => Command::none(),
+            Err(e) => error::report(Error::SaveLibrary(Library::path(&self.data_dir), e)),
+        }
+    }
+
+    fn overview(&mut self, theme: &Theme) -> Element<'_, Message> {
+        let name_input = TextInput::new(
+            &mut self.name_input,
+            "New playlist...",
+            &self.name_value,
+            |name| Message::Library(LibraryMessage::NameChanged(name)),
+        )
+        .on_submit(Message::Library(LibraryMessage::NameSubmitted()))
+        .style(theme.search_input_theme());
+        let mut create_btn = Button::new(&mut self.name_btn_state, Text::new("Create"))
+            .style(theme.tab_button_theme());
+        if !self.name_value.trim().is_empty() {
+            create_btn = create_btn.on_press(Message::Library(LibraryMessage::NameSubmitted()));
+        }
+        let import_btn = Button::new(&mut self.import_btn_state, Text::new("Import"))
+            .on_press(Message::Library(LibraryMessage::Import()))
+            .style(theme.tab_button_theme());
+        let mut column = Column::new().spacing(10).push(
+            Row::new()
+                .push(name_input)
+                .push(create_btn)
+                .push(import_btn)
+                .spacing(10)
+                .align_items(Align::Center),
+        );
+        if let Some(progress) = progress(&self.enqueuing, &mut self.cancel_btn_state, theme) {
+            column = column.push(progress);
+        }
+        if let Some((track, _)) = self.picking.as_ref() {
+            let cancel_btn = Button::new(&mut self.cancel_pick_btn_state, Text::new("Cancel"))
+                .on_press(Message::Library(LibraryMessage::CancelPick()))
+                .style(theme.tab_button_theme());
+            column = column.push(
+                Row::new()
+                    .push(
+                        Text::new(format!("Pick or create a playlist for {}", track.title))
+                            .width(Length::Fill),
+                    )
+                    .push(cancel_btn)
+                    .push(Space::new(Length::Units(10), Length::Shrink))
+                    .align_items(Align::Center),
+            );
+        }
+
+        while self.btn_states.len() < self.library.playlists.len() {
+            self.btn_states.push(Default::default());
+        }
+        let mut list = Column::new().width(Length::Fill);
+        if let Some((name, problems)) = self.import_problems.as_ref() {
+            let dismiss_btn =
+                Button::new(&mut self.dismiss_btn_state, Text::new("Dismiss").size(16))
+                    .on_press(Message::Library(LibraryMessage::DismissProblems()))
+                    .style(theme.tab_button_theme());
+            list = list.push(
+                Row::new()
+                    .push(
+                        Text::new(format!("Skipped while importing {}:", name)).width(Length::Fill),
+                    )
+                    .push(dismiss_btn)
+                    .push(Space::new(Length::Units(10), Length::Shrink))
+                    .align_items(Align::Center),
+            );
+            for problem in problems.iter() {
+                list = list.push(Text::new(problem.as_str()).size(16));
+            }
+            list = list.push(Space::new(Length::Shrink, Length::Units(10)));
+        }
+        if self.library.playlists.is_empty() {
+            list = list.push(Text::new("No playlists yet, enter a name to create one"));
+        }
+        let len = self.library.playlists.len();
+        for (i, (playlist, [open_state, up_state, down_state])) in self
+            .library
+            .playlists
+            .iter()
+            .zip(self.btn_states.iter_mut())
+            .enumerate()
+        {
+            let row = Row::new()
+                .push(
+                    Text::new(playlist.name.clone())
+                        .width(Length::Fill)
+                        .horizontal_alignment(HorizontalAlignment::Left),
+                )
+                .push(Space::new(Length::Units(10), Length::Shrink))
+                .push(Text::new(format!(
+                    "{} tracks, {}",
+                    playlist.tracks.len(),
+                    duration_fmt(&playlist.len())
+                )))
+                .width(Length::Fill);
+            let open_msg = match self.picking {
+                Some(_) => LibraryMessage::Picked(i),
+                None => LibraryMessage::Opened(Some(i)),
+            };
+            let open_btn = Button::new(open_state, row)
+                .on_press(Message::Library(open_msg))
+                .style(theme.tab_button_theme())
+                .width(Length::Fill);
+            let move_playlist = |to: usize| Message::Library(LibraryMessage::MovePlaylist(i, to));
+            let mut up_btn =
+                Button::new(up_state, Text::new("Up").size(16)).style(theme.tab_button_theme());
+            let mut down_btn =
+                Button::new(down_state, Text::new("Down").size(16)).style(theme.tab_button_theme());
+            //Buttons without a message are disabled
+            if i > 0 {
+                up_btn = up_btn.on_press(move_playlist(i - 1));
+            }
+            if i + 1 < len {
+                down_btn = down_btn.on_press(move_playlist(i + 1));
+            }
+            list = list.push(
+                Row::new()
+                    .push(open_btn)
+                    .push(up_btn)
+                    .push(down_btn)
+                    .push(Space::new(Length::Units(10), Length::Shrink))
+                    .spacing(5)
+                    .height(Length::Units(ROW_HEIGHT))
+                    .align_items(Align::Center),
+            );
+        }
+
+        column
+            .push(
+                Scrollable::new(&mut self.scroll)
+                    .push(list)
+                    .height(Length::Fill),
+            )
+            .into()
+    }
+
+    fn playlist_view(&mut self, open: usize, theme: &Theme) -> Element<'_, Message> {
+        let playlist = &self.library.playlists[open];
+        let back_btn = Button::new(&mut self.back_btn_state, Text::new("Back"))
+            .on_press(Message::Library(LibraryMessage::Opened(None)))
+            .style(theme.tab_button_theme());
+        let name_input =
+            TextInput::new(&mut self.name_input, "Name...", &self.name_value, |name| {
+                Message::Library(LibraryMessage::NameChanged(name))
+            })
+            .on_submit(Message::Library(LibraryMessage::NameSubmitted()))
+            .style(theme.search_input_theme());
+        let mut rename_btn = Button::new(&mut self.name_btn_state, Text::new("Rename"))
+            .style(theme.tab_button_theme());
+        let name = self.name_value.trim();
+        if !name.is_empty() && name != playlist.name {
+            rename_btn = rename_btn.on_press(Message::Library(LibraryMessage::NameSubmitted()));
+        }
+        let mut enqueue_btn = Button::new(&mut self.enqueue_btn_state, Text::new("Enqueue all"))
+            .style(theme.tab_button_theme());
+        let mut shuffle_btn =
+            Button::new(&mut self.shuffle_btn_state, Text::new("Enqueue shuffled"))
+                .style(theme.tab_button_theme());
+        if !playlist.tracks.is_empty() && self.enqueuing.is_none() {
+            enqueue_btn = enqueue_btn.on_press(Message::Library(LibraryMessage::Enqueue(false)));
+            shuffle_btn = shuffle_btn.on_press(Message::Library(LibraryMessage::Enqueue(true)));
+        }
+        let delete_text = match self.confirm_delete {
+            true => "Really delete?",
+            false => "Delete",
+        };
+        let export_btn = Button::new(&mut self.export_btn_state, Text::new("Export"))
+            .on_press(Message::Library(LibraryMessage::Export()))
+            .style(theme.tab_button_theme());
+        let delete_btn = Button::new(&mut self.delete_btn_state, Text::new(delete_text))
+            .on_press(Message::Library(LibraryMessage::Delete()))
+            .style(theme.tab_button_theme());
+
+        let mut column = Column::new()
+            .spacing(10)
+            .push(
+                Row::new()
+                    .push(back_btn)
+                    .push(name_input)
+                    .push(rename_btn)
+                    .spacing(10)
+                    .align_items(Align::Center),
+            )
+            .push(
+                Row::new()
+                    .push(enqueue_btn)
+                    .push(shuffle_btn)
+                    .push(Space::new(Length::Fill, Length::Shrink))
+                    .push(export_btn)
+                    .push(delete_btn)
+                    .spacing(10)
+                    .align_items(Align::Center),
+            );
+        if let Some(progress) = progress(&self.enqueuing, &mut self.cancel_btn_state, theme) {
+            column = column.push(progress);
+        }
+
+        while self.btn_states.len() < playlist.tracks.len() {
+            self.btn_states.push(Default::default());
+        }
+        let drop_indicator = || {
+            Container::new(Space::new(
+                Length::Fill,
+                Length::Units(DROP_INDICATOR_HEIGHT),
+            ))
+            .style(theme.drop_indicator_theme())
+        };
+        let mut list = Column::new().width(Length::Fill);
+        if playlist.tracks.is_empty() {
+            if self.drop_target.is_some() {
+                list = list.push(drop_indicator());
+            }
+            let hint = Row::new()
+                .push(Text::new(
+                    "Drag tracks from the queue, the history or the search here",
+                ))
+                .width(Length::Fill)
+                .height(Length::Units(ROW_HEIGHT))
+                .align_items(Align::Center);
+            let mut hint = DragArea::new(hint);
+            if self.dragging {
+                hint = hint.on_hover(|_, _| Message::Drag(DragMessage::OverEntry(0, 0.0)));
+            }
+            list = list.push(hint);
+        }
+        let len = playlist.tracks.len();
+        for (i, (track, [up_state, down_state, remove_state])) in playlist
+            .tracks
+            .iter()
+            .zip(self.btn_states.iter_mut())
+            .enumerate()
+        {
+            let row = Row::new()
+                .push(Text::new(format!("{}.", i + 1)).width(Length::Units(30)))
+                .push(Space::new(Length::Units(10), Length::Shrink))
+                .push(
+                    Text::new(track.title.clone())
+                        .width(Length::Fill)
+                        .horizontal_alignment(HorizontalAlignment::Left),
+                )
+                .push(Space::new(Length::Units(10), Length::Shrink))
+                .push(Text::new(track_len_fmt(&track.track())))
+                .width(Length::Fill)
+                .height(Length::Units(ROW_HEIGHT))
+                .align_items(Align::Center);
+            let mut row = DragArea::new(row)
+                .on_press(Message::Drag(DragMessage::Pressed(DragItem::Saved(i))));
+            if self.dragging {
+                row = row.on_hover(move |fraction, _| {
+                    Message::Drag(DragMessage::OverEntry(i, fraction))
+                });
+            }
+            let move_track = |to: usize| Message::Library(LibraryMessage::MoveTrack(i, to));
+            let mut up_btn =
+                Button::new(up_state, Text::new("Up").size(16)).style(theme.tab_button_theme());
+            let mut down_btn =
+                Button::new(down_state, Text::new("Down").size(16)).style(theme.tab_button_theme());
+            let remove_btn = Button::new(remove_state, Text::new("Remove").size(16))
+                .on_press(Message::Library(LibraryMessage::RemoveTrack(i)))
+                .style(theme.tab_button_theme());
+            if i > 0 {
+                up_btn = up_btn.on_press(move_track(i - 1));
+            }
+            if i + 1 < len {
+                down_btn = down_btn.on_press(move_track(i + 1));
+            }
+            if self.drop_target == Some(i) {
+                list = list.push(drop_indicator());
+            }
+            list = list.push(
+                Row::new()
+                    .push(row)
+                    .push(up_btn)
+                    .push(down_btn)
+                    .push(remove_btn)
+                    .push(Space::new(Length::Units(10), Length::Shrink))
+                    .spacing(5)
+                    .height(Length::Units(ROW_HEIGHT))
+                    .align_items(Align::Center),
+            );
+        }
+        if len > 0 && self.drop_target == Some(len) {
+            list = list.push(drop_indicator());
+        }
+
+        let list = Scrollable::new(&mut self.scroll)
+            .push(list)
+            .height(Length::Fill);
+        let mut list = DragArea::new(list);
+        if self.dragging {
+            list = list
+                .on_hover(|fraction, height| Message::Drag(DragMessage::OverList(fraction, height)))
+                .on_leave(Message::Drag(DragMessage::LeftList()));
+        }
+        column.push(list).into()
+    }
+}
+
+/// Progress of enqueuing a playlist, with a button to stop it
+fn progress<'a>(
+    enqueuing: &Option<Enqueuing>,
+    cancel_state: &'a mut iced::button::State,
+    theme: &Theme,
+) -> Option<Row<'a, Message>> {
+    let enqueuing = enqueuing.as_ref()?;
+    let cancel_btn = Button::new(cancel_state, Text::new("Cancel").size(16))
+        .on_press(Message::Library(LibraryMessage::CancelEnqueue()))
+        .style(theme.tab_button_theme());
+    let text = format!(
+        "Enqueuing {}: {} of {}",
+        enqueuing.name,
+        enqueuing.total - enqueuing.urls.len(),
+        enqueuing.total
+    );
+    Some(
+        Row::new()
+            .push(Text::new(text).width(Length::Fill))
+            .push(cancel_btn)
+            .spacing(10)
+            .align_items(Align::Center),
+    )
+}
+
+impl Tab for LibraryTab {
+    type Message = Message;
+
+    fn title(&self) -> String {
+        match self.open_playlist() {
+            Some(playlist) => playlist.name.clone(),
+            None => String::from("Saved Playlists"),
+        }
+    }
+
+    fn tab_label(&self) -> (Option<Icon>, String) {
+        (Icon::LibraryMusic.into(), "Library".to_string())
+    }
+
+    fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message> {
+        match self.open {
+            Some(open) => self.playlist_view(open, theme),
+            None => self.overview(theme),
+        }
+    }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
+    }
+}
@@ -0,0 +1,213 @@
+use crate::icons::Icon;
+use crate::log::{buffered_records, LogRecord};
+use crate::tabs::Tab;
+use crate::theme::Theme;
+use crate::Message;
+use iced::{
+    Align, Button, Checkbox, Clipboard, Column, Command, Element, Length, Radio, Row, Scrollable,
+    Space, Text, TextInput,
+};
+use log::Level;
+use std::path::PathBuf;
+
+/// Rendering thousands of lines makes the layout sluggish
+const MAX_SHOWN_RECORDS: usize = 500;
+
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+#[derive(Debug, Clone)]
+pub enum LogsMessage {
+    Refresh(),
+    LevelSelected(Level),
+    FilterChanged(String),
+    Copy(),
+    OpenFolder(),
+    SessionCaptureToggled(bool),
+    /// Handled by the companion, which knows everything going into the bundle
+    CreateDiagnostics(),
+    DiagnosticsCreated(Result<Option<PathBuf>, String>),
+}
+
+#[derive(Debug)]
+pub struct LogsTab {
+    log_dir: PathBuf,
+    records: Vec<LogRecord>,
+    level: Level,
+    filter: String,
+    status: Option<String>,
+    include_session: bool,
+
+    scroll: iced::scrollable::State,
+    filter_input: iced::text_input::State,
+    copy_button_state: iced::button::State,
+    open_button_state: iced::button::State,
+    diagnostics_button_state: iced::button::State,
+}
+
+impl LogsTab {
+    pub fn new(log_dir: PathBuf) -> Self {
+        LogsTab {
+            log_dir,
+            records: Vec::new(),
+            level: Level::Info,
+            filter: String::new(),
+            status: None,
+            include_session: false,
+            scroll: Default::default(),
+            filter_input: Default::default(),
+            copy_button_state: Default::default(),
+            open_button_state: Default::default(),
+            diagnostics_button_state: Default::default(),
+        }
+    }
+
+    pub fn update(&mut self, clipboard: &mut Clipboard, message: LogsMessage) -> Command<Message> {
+        match message {
+            LogsMessage::Refresh() => self.records = buffered_records(),
+            LogsMessage::LevelSelected(level) => self.level = level,
+            LogsMessage::FilterChanged(filter) => self.filter = filter,
+            LogsMessage::Copy() => {
+                let text = self
+                    .filtered()
+                    .map(|r| r.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                clipboard.write(text);
+                self.status = Some(String::from("Copied to clipboard"));
+            }
+            LogsMessage::OpenFolder() => {
+                if let Err(e) = open::that(&self.log_dir) {
+                    self.status = Some(format!("Could not open {:?}: {}", self.log_dir, e));
+                }
+            }
+            LogsMessage::SessionCaptureToggled(include) => self.include_session = include,
+            LogsMessage::CreateDiagnostics() => {}
+            LogsMessage::DiagnosticsCreated(res) => {
+                self.status = match res {
+                    Ok(Some(path)) => Some(format!("Diagnostic bundle saved to {:?}", path)),
+                    Ok(None) => None,
+                    Err(e) => Some(format!("Could not create diagnostic bundle: {}", e)),
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    /// Whether the diagnostic bundle should contain the records and state of this session
+    pub fn include_session(&self) -> bool {
+        self.include_session
+    }
+
+    fn filtered(&self) -> impl Iterator<Item = &LogRecord> {
+        let filter = self.filter.to_lowercase();
+        let level = self.level;
+        self.records.iter().filter(move |r| {
+            r.level <= level
+                && (filter.is_empty()
+                    || r.message.to_lowercase().contains(&filter)
+                    || r.target.to_lowercase().contains(&filter))
+        })
+    }
+}
+
+impl Tab for LogsTab {
+    type Message = Message;
+
+    fn title(&self) -> String {
+        String::from("Logs")
+    }
+
+    fn tab_label(&self) -> (Option<Icon>, String) {
+        (Icon::Subject.into(), "Logs".to_string())
+    }
+
+    fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message> {
+        let mut levels = Row::new().spacing(10);
+        for level in LEVELS.iter() {
+            levels = levels.push(
+                Radio::new(*level, &level.to_string(), Some(self.level), |l| {
+                    Message::Logs(LogsMessage::LevelSelected(l))
+                })
+                .style(theme.radio_button_theme()),
+            );
+        }
+
+        let mut lines = Column::new().width(Length::Fill);
+        let records: Vec<_> = self.filtered().collect();
+        let skip = records.len().saturating_sub(MAX_SHOWN_RECORDS);
+        for record in records.iter().skip(skip) {
+            let color = match record.level {
+                Level::Error => theme.error_color(),
+                _ => theme.text_color(),
+            };
+            lines = lines.push(Text::new(record.to_string()).size(14).color(color));
+        }
+
+        let filter_input = TextInput::new(
+            &mut self.filter_input,
+            "Filter...",
+            &self.filter,
+            |f| Message::Logs(LogsMessage::FilterChanged(f)),
+        )
+        .padding(5)
+        .style(theme.search_input_theme());
+        let mut actions = Row::new()
+            .spacing(10)
+            .push(filter_input)
+            .push(
+                Button::new(&mut self.copy_button_state, Text::new("Copy"))
+                    .on_press(Message::Logs(LogsMessage::Copy()))
+                    .style(theme.tab_button_theme()),
+            )
+            .push(
+                Button::new(&mut self.open_button_state, Text::new("Open Log Folder"))
+                    .on_press(Message::Logs(LogsMessage::OpenFolder()))
+                    .style(theme.tab_button_theme()),
+            )
+            .push(Space::new(Length::Units(10), Length::Shrink));
+        let diagnostics = Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(
+                Button::new(
+                    &mut self.diagnostics_button_state,
+                    Text::new("Create Diagnostic Bundle"),
+                )
+                .on_press(Message::Logs(LogsMessage::CreateDiagnostics()))
+                .style(theme.tab_button_theme()),
+            )
+            .push(
+                Checkbox::new(self.include_session, "Include Session Capture", |b| {
+                    Message::Logs(LogsMessage::SessionCaptureToggled(b))
+                })
+                .text_size(16)
+                .style(*theme),
+            );
+        if let Some(status) = self.status.as_ref() {
+            actions = actions.push(Text::new(status.as_str()).size(14).color(theme.text_color()));
+        }
+
+        Column::new()
+            .spacing(10)
+            .push(levels)
+            .push(actions)
+            .push(diagnostics)
+            .push(
+                Scrollable::new(&mut self.scroll)
+                    .push(lines)
+                    .height(Length::Fill),
+            )
+            .into()
+    }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
+    }
+}
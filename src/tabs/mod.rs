@@ -1,18 +1,25 @@
 use crate::icons::Icon;
 use crate::theme::Theme;
+use crate::util::drag::DragArea;
 use iced::{Align, Button, Column, Command, Container, Element, Length, Row, Space, Text};
 use std::borrow::BorrowMut;
 use std::cmp::min;
 use std::convert::TryInto;
 use std::fmt::{Debug, Formatter};
 
+pub mod activity;
 pub mod history;
+pub mod library;
+pub mod logs;
 pub mod playlist;
 pub mod search;
 pub mod settings;
 
 pub const HEADER_SIZE: u16 = 32;
 pub const TAB_PADDING: u16 = 16;
+/// Height of every line of a list tracks can be dropped on, so its size is known for scrolling
+pub const ROW_HEIGHT: u16 = 32;
+pub const DROP_INDICATOR_HEIGHT: u16 = 2;
 
 pub struct Tabs<M, const SIZE: usize>
 where
@@ -44,23 +51,30 @@ where
             .expect("Could build Button State Array");
 
         Tabs {
-            cur_tab,
+            cur_tab: min(SIZE - 1, cur_tab),
             label,
             on_select: Box::new(on_select),
         }
     }
 
     pub fn update(&mut self, cur: usize) -> Command<M> {
-        self.cur_tab = min(SIZE, cur);
+        self.cur_tab = min(SIZE - 1, cur);
         Command::none()
     }
 
+    pub fn cur_tab(&self) -> usize {
+        self.cur_tab
+    }
+
+    /// While `dragging`, hovering a label opens its tab, so tracks can be dropped there
     pub fn view<'a>(
         &'a mut self,
         tabs: [&'a mut dyn Tab<Message = M>; SIZE],
         theme: &Theme,
+        dragging: bool,
     ) -> (Element<'a, M>, Element<'a, M>) {
         let mut tabs_column = Column::new();
+        let on_select = &self.on_select;
         for (i, (tab, label_state)) in tabs.iter().zip(self.label.iter_mut()).enumerate() {
             let (icon, label_text) = tab.tab_label();
             let mut label = Row::new();
@@ -72,11 +86,15 @@ where
             label = label.push(Text::new(label_text));
             let mut tab_button = Button::new(label_state, label)
                 .width(Length::Fill)
-                .on_press((self.on_select)(i))
+                .on_press(on_select(i))
                 .style(theme.tab_button_theme());
             if self.cur_tab == i {
                 tab_button = tab_button.style(theme.selected_tab_button_theme())
             }
+            let mut tab_button = DragArea::new(tab_button);
+            if dragging && self.cur_tab != i {
+                tab_button = tab_button.on_hover(move |_, _| on_select(i));
+            }
 
             let container = Container::new(tab_button).width(Length::Fill);
             tabs_column = tabs_column.push(container);
@@ -134,4 +152,6 @@ pub trait Tab: Debug {
     }
 
     fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message>;
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State;
 }
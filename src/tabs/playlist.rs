@@ -1,27 +1,43 @@
+use crate::drag_state::{DragItem, DragMessage};
+use crate::error::{self, Error};
 use crate::icons::Icon;
-use crate::tabs::Tab;
+use crate::library::{SavedPlaylist, SavedTrack};
+use crate::playlist_file;
+use crate::tabs::{Tab, DROP_INDICATOR_HEIGHT, ROW_HEIGHT};
 use crate::theme::Theme;
-use crate::util::duration_fmt;
+use crate::toast::{self, Toast};
+use crate::util::drag::DragArea;
+use crate::util::{scroll_offset, set_scroll_offset, track_len_fmt};
 use crate::Connection;
-use crate::{Message, MAX_DOUBLE_CLICK_INTERVAL};
+use crate::{double_click_interval, Message};
 use iced::{
-    Button, Column, Command, Element, HorizontalAlignment, Length, Row, Scrollable, Space, Text,
+    Align, Button, Column, Command, Container, Element, HorizontalAlignment, Length, Row,
+    Scrollable, Space, Text,
 };
 use reciprocity_communication::messages::{PlayerControl, PlayerState, Track};
+use reqwest::Url;
 use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub enum PlaylistMessage {
     PlayerStateChanged(Option<PlayerState>),
     SongClicked(usize),
+    /// Exports the queue as last reported by the bot
+    Export(),
 }
 
 #[derive(Debug)]
 pub struct PlaylistTab {
     playlist: Vec<Track>,
+    /// True while a track is dragged that can be added to the queue
+    dragging: bool,
+    /// Set while the dragged track is over the queue, which always adds at its end
+    drop_target: Option<usize>,
     scroll: iced::scrollable::State,
     last_click: (usize, Instant),
     btn_states: Vec<iced::button::State>,
+    save_btn_states: Vec<iced::button::State>,
+    export_btn_state: iced::button::State,
 }
 
 impl PlaylistTab {
@@ -29,9 +45,13 @@ impl PlaylistTab {
         //TODO
         PlaylistTab {
             playlist: Vec::new(),
+            dragging: false,
+            drop_target: None,
             scroll: Default::default(),
             last_click: (0, Instant::now()),
             btn_states: Vec::new(),
+            save_btn_states: Vec::new(),
+            export_btn_state: Default::default(),
         }
     }
 
@@ -46,7 +66,7 @@ impl PlaylistTab {
             }
             PlaylistMessage::SongClicked(i) => {
                 if self.last_click.0 == i
-                    && self.last_click.1.elapsed() <= MAX_DOUBLE_CLICK_INTERVAL
+                    && self.last_click.1.elapsed() <= double_click_interval()
                 {
                     self.last_click = (0, Instant::now());
                     if let Some(con) = con {
@@ -55,10 +75,58 @@ impl PlaylistTab {
                 }
                 self.last_click = (i, Instant::now());
             }
+            PlaylistMessage::Export() => {
+                return playlist_file::export(SavedPlaylist {
+                    name: String::from("Queue"),
+                    tracks: self.playlist.iter().map(SavedTrack::from).collect(),
+                });
+            }
         }
 
         Command::none()
     }
+
+    /// Tracks of the queue itself can not be moved, so only other tracks are taken
+    pub fn set_drag(&mut self, item: Option<&DragItem>, target: Option<usize>) {
+        self.dragging = matches!(item, Some(DragItem::Track(_)) | Some(DragItem::Saved(_)));
+        self.drop_target = target
+            .filter(|_| self.dragging)
+            .map(|_| self.playlist.len());
+    }
+
+    /// Scrolls while dragging near the edges, within the list of the given height
+    pub fn scroll_by(&mut self, delta: f32, height: f32) {
+        //An empty queue still shows a line to drop on
+        let rows = self.playlist.len() + 1;
+        let mut content = (rows * ROW_HEIGHT as usize) as f32;
+        if self.drop_target.is_some() {
+            content += DROP_INDICATOR_HEIGHT as f32;
+        }
+        let max = (content - height).max(0.0);
+        let offset = (scroll_offset(&self.scroll) + delta).max(0.0).min(max);
+        set_scroll_offset(&mut self.scroll, offset);
+    }
+
+    /// Adds a dropped track at the end of the queue
+    pub fn drop(&mut self, con: &Option<Connection>, track: Track) -> Command<Message> {
+        let connection = match con {
+            Some(con) => con,
+            None => return Command::none(),
+        };
+        let url = match Url::parse(&track.uri) {
+            Ok(url) => url,
+            Err(_) => return error::report(Error::InvalidUrl(track.uri)),
+        };
+        Command::batch(vec![
+            connection.control_request(PlayerControl::Enqueue(url)),
+            toast::show(Toast::success(format!("Added: {}", track.title))),
+        ])
+    }
+
+    /// Track at the index of the queue as shown
+    pub fn track(&self, i: usize) -> Option<&Track> {
+        self.playlist.get(i)
+    }
 }
 
 impl Tab for PlaylistTab {
@@ -77,17 +145,39 @@ impl Tab for PlaylistTab {
 
         while self.btn_states.len() <= self.playlist.len() {
             self.btn_states.push(Default::default());
+            self.save_btn_states.push(Default::default());
+        }
+
+        if !self.playlist.is_empty() {
+            let export_btn = Button::new(&mut self.export_btn_state, Text::new("Export"))
+                .on_press(Message::Playlist(PlaylistMessage::Export()))
+                .style(theme.tab_button_theme());
+            column = column.push(
+                Row::new()
+                    .push(Space::new(Length::Fill, Length::Shrink))
+                    .push(export_btn)
+                    .push(Space::new(Length::Units(15), Length::Shrink))
+                    .height(Length::Units(ROW_HEIGHT))
+                    .align_items(Align::Center),
+            );
         }
+        let drop_indicator = || {
+            Container::new(Space::new(
+                Length::Fill,
+                Length::Units(DROP_INDICATOR_HEIGHT),
+            ))
+            .style(theme.drop_indicator_theme())
+        };
 
-        for (i, (track, btn_state)) in self
+        for (i, ((track, btn_state), save_btn_state)) in self
             .playlist
             .iter()
             .zip(self.btn_states.iter_mut())
+            .zip(self.save_btn_states.iter_mut())
             .enumerate()
         {
-            let i = i + 1;
             let row = Row::new()
-                .push(Text::new(format!("{}.", i)).width(Length::Units(30)))
+                .push(Text::new(format!("{}.", i + 1)).width(Length::Units(30)))
                 .push(Space::new(Length::Units(10), Length::Shrink))
                 .push(
                     Text::new(track.title.clone())
@@ -95,19 +185,61 @@ impl Tab for PlaylistTab {
                         .horizontal_alignment(HorizontalAlignment::Left),
                 )
                 .push(Space::new(Length::Units(10), Length::Shrink))
-                .push(Text::new(duration_fmt(&track.len)))
-                .push(Space::new(Length::Units(15), Length::Shrink))
+                .push(Text::new(track_len_fmt(track)))
                 .width(Length::Fill);
             let btn = Button::new(btn_state, row)
-                .on_press(Message::Playlist(PlaylistMessage::SongClicked(i)))
-                .style(theme.tab_button_theme());
-
-            column = column.push(btn);
+                .on_press(Message::Playlist(PlaylistMessage::SongClicked(i + 1)))
+                .style(theme.tab_button_theme())
+                .width(Length::Fill);
+            let mut btn = DragArea::new(btn)
+                .on_press(Message::Drag(DragMessage::Pressed(DragItem::Queued(i))));
+            if self.dragging {
+                btn = btn.on_hover(move |fraction, _| {
+                    Message::Drag(DragMessage::OverEntry(i, fraction))
+                });
+            }
+            let save_btn = Button::new(save_btn_state, Icon::PlaylistAdd.get_svg(theme))
+                .on_press(Message::AddToPlaylist(track.clone()))
+                .style(theme.tab_button_theme())
+                .width(Length::Units(30));
+            column = column.push(
+                Row::new()
+                    .push(btn)
+                    .push(save_btn)
+                    .push(Space::new(Length::Units(15), Length::Shrink))
+                    .height(Length::Units(ROW_HEIGHT))
+                    .align_items(Align::Center),
+            );
+        }
+        if self.drop_target.is_some() {
+            column = column.push(drop_indicator());
+        }
+        if self.playlist.is_empty() && self.dragging {
+            let hint = Row::new()
+                .push(Text::new(
+                    "Drag tracks from the history, the search or a playlist here",
+                ))
+                .width(Length::Fill)
+                .height(Length::Units(ROW_HEIGHT))
+                .align_items(Align::Center);
+            column = column.push(
+                DragArea::new(hint).on_hover(|_, _| Message::Drag(DragMessage::OverEntry(0, 0.0))),
+            );
         }
 
-        Scrollable::new(&mut self.scroll)
+        let list = Scrollable::new(&mut self.scroll)
             .push(column)
-            .height(Length::Fill)
-            .into()
+            .height(Length::Fill);
+        let mut list = DragArea::new(list);
+        if self.dragging {
+            list = list
+                .on_hover(|fraction, height| Message::Drag(DragMessage::OverList(fraction, height)))
+                .on_leave(Message::Drag(DragMessage::LeftList()));
+        }
+        list.into()
+    }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
     }
 }
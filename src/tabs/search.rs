@@ -1,19 +1,22 @@
+use crate::config::DEFAULT_SEARCH_BACKEND;
 use crate::connection::Connection;
+use crate::drag_state::{DragItem, DragMessage};
+use crate::error::{self, Error};
 use crate::icons::Icon;
 use crate::tabs::Tab;
 use crate::theme::Theme;
+use crate::toast::{self, Toast};
+use crate::util::drag::DragArea;
 use crate::util::youtube::{search, Video};
-use crate::util::{get_image, get_image_uri_from_yt_id};
-use crate::{Message, MAX_DOUBLE_CLICK_INTERVAL};
+use crate::util::{duration_parse, get_image, get_image_uri_from_yt_id};
+use crate::{double_click_interval, Message, PLAYLIST_TAB};
 use iced::{
-    Button, Column, Command, Element, Image, Length, Row, Scrollable, Space, Text, TextInput,
-    Tooltip,
+    Align, Button, Column, Command, Element, Image, Length, Row, Scrollable, Space, Text, TextInput,
 };
-use reciprocity_communication::messages::PlayerControl;
+use reciprocity_communication::messages::{PlayerControl, Track};
+use log::{trace, warn};
 use reqwest::Url;
-use std::time::{Duration, Instant};
-
-const TOOLTIP_DURATION: Duration = Duration::from_secs(2);
+use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub enum SearchMessage {
@@ -22,7 +25,8 @@ pub enum SearchMessage {
     SearchResult(String, Vec<Video>),
     InputChanged(String),
     InputSubmit(),
-    UpdateUi(),
+    Search(String),
+    BackendChanged(String),
 }
 
 #[derive(Debug)]
@@ -31,13 +35,13 @@ pub struct SearchTab {
     search_input: iced::text_input::State,
     search_input_value: String,
 
-    tooltip: (String, Instant),
-
+    backend: String,
     search: String,
     results: Vec<(Option<iced::image::Handle>, Video)>,
 
     last_click: (usize, Instant),
     btn_states: Vec<iced::button::State>,
+    save_btn_states: Vec<iced::button::State>,
 }
 
 impl SearchTab {
@@ -47,25 +51,53 @@ impl SearchTab {
             scroll: Default::default(),
             search_input: Default::default(),
             search_input_value: "".to_string(),
-            tooltip: ("".to_string(), Instant::now()),
+            backend: DEFAULT_SEARCH_BACKEND.to_string(),
             search: "".to_string(),
             results: Vec::new(),
             last_click: (0, Instant::now()),
             btn_states: Vec::new(),
+            save_btn_states: Vec::new(),
         }
     }
 
+    /// Restores the search of a previous session, downloading the thumbnails again
+    pub fn restore(&mut self, query: String, results: Vec<Video>) -> Command<Message> {
+        self.search_input_value = query.clone();
+        self.search = query.clone();
+        self.update(&None, SearchMessage::SearchResult(query, results))
+    }
+
+    pub fn query(&self) -> &str {
+        &self.search
+    }
+
+    /// Puts the cursor into the search input, after the current query
+    pub fn focus_input(&mut self) {
+        self.search_input.focus();
+        self.search_input.move_cursor_to_end();
+    }
+
+    pub fn results(&self) -> Vec<Video> {
+        self.results.iter().map(|(_, v)| v.clone()).collect()
+    }
+
     pub fn update(&mut self, con: &Option<Connection>, message: SearchMessage) -> Command<Message> {
         match message {
             SearchMessage::InputChanged(i) => self.search_input_value = i,
             SearchMessage::InputSubmit() => {
-                self.search = self.search_input_value.clone();
-                return Command::perform(search(self.search.clone()), move |res| match res {
-                    Ok((query, videos)) => {
-                        Message::Search(SearchMessage::SearchResult(query, videos))
-                    }
-                    Err(e) => panic!("Search Error: {:?}", e),
-                });
+                return self.update(con, SearchMessage::Search(self.search_input_value.clone()));
+            }
+            SearchMessage::Search(query) => {
+                self.search = query.clone();
+                return Command::perform(
+                    search(self.backend.clone(), query.clone()),
+                    move |res| match res {
+                        Ok((query, videos)) => {
+                            Message::Search(SearchMessage::SearchResult(query, videos))
+                        }
+                        Err(e) => Message::Error(Error::Search(query.clone(), e)),
+                    },
+                );
             }
             SearchMessage::SearchResult(q, v) => {
                 if self.search.eq(&q) {
@@ -74,9 +106,14 @@ impl SearchTab {
                     let mut commands = Vec::new();
                     for (_, video) in self.results.iter() {
                         let id = video.id.clone();
-                        let img_url =
-                            get_image_uri_from_yt_id(id.clone()).expect("Error creating Image Uri");
-                        println!("{:?}", id);
+                        let img_url = match get_image_uri_from_yt_id(id.clone()) {
+                            Ok(url) => url,
+                            Err(e) => {
+                                warn!("Invalid thumbnail url video={}: {}", id, e);
+                                continue;
+                            }
+                        };
+                        trace!("Loading thumbnail video={}", id);
                         commands.push(Command::perform(
                             async move {
                                 let res = get_image(img_url).await;
@@ -85,7 +122,7 @@ impl SearchTab {
                             |(id, res)| match res {
                                 Ok(img) => Message::Search(SearchMessage::SearchImage(id, img)),
                                 Err(e) => {
-                                    println!("{}", e);
+                                    warn!("Could not load thumbnail video={}: {}", id, e);
                                     Message::None()
                                 }
                             },
@@ -102,33 +139,27 @@ impl SearchTab {
                 .collect(),
             SearchMessage::SearchClick(i) => {
                 if self.last_click.0 == i
-                    && self.last_click.1.elapsed() <= MAX_DOUBLE_CLICK_INTERVAL
+                    && self.last_click.1.elapsed() <= double_click_interval()
                 {
                     self.last_click = (0, Instant::now());
                     if let Some(con) = con {
                         if let Some((_, song)) = self.results.get(i - 1) {
-                            let url = Url::parse(&song.url).expect("Error Parsing Url");
-                            self.tooltip = (song.title.clone(), Instant::now());
+                            let url = match Url::parse(&song.url) {
+                                Ok(url) => url,
+                                Err(_) => return error::report(Error::InvalidUrl(song.url.clone())),
+                            };
+                            let added = Toast::success(format!("Added: {}", song.title))
+                                .on_click(Message::TabSelected(PLAYLIST_TAB));
                             return Command::batch(vec![
                                 con.control_request(PlayerControl::Enqueue(url)),
-                                Command::perform(
-                                    async {
-                                        tokio::time::sleep(
-                                            TOOLTIP_DURATION + Duration::from_millis(200),
-                                        )
-                                        .await
-                                    },
-                                    |_| Message::Search(SearchMessage::UpdateUi()),
-                                ),
+                                toast::show(added),
                             ]);
                         }
                     }
                 }
                 self.last_click = (i, Instant::now());
             }
-            SearchMessage::UpdateUi() => {
-                //Ignore
-            }
+            SearchMessage::BackendChanged(backend) => self.backend = backend,
         }
 
         Command::none()
@@ -152,6 +183,7 @@ impl Tab for SearchTab {
 
         while self.btn_states.len() <= self.results.len() {
             self.btn_states.push(Default::default());
+            self.save_btn_states.push(Default::default());
         }
 
         let search_input = TextInput::new(
@@ -165,10 +197,11 @@ impl Tab for SearchTab {
         column = column.push(search_input);
 
         let mut results_column = Scrollable::new(&mut self.scroll).height(Length::Fill).width(Length::Fill);
-        for (i, ((img, video), btn_state)) in self
+        for (i, (((img, video), btn_state), save_btn_state)) in self
             .results
             .iter()
             .zip(self.btn_states.iter_mut())
+            .zip(self.save_btn_states.iter_mut())
             .enumerate()
         {
             let i = i + 1;
@@ -198,24 +231,31 @@ impl Tab for SearchTab {
                 .on_press(Message::Search(SearchMessage::SearchClick(i)))
                 .style(theme.tab_button_theme())
                 .width(Length::Fill);
+            let track = Track {
+                len: duration_parse(&video.duration).unwrap_or_default(),
+                pos: Default::default(),
+                title: video.title.clone(),
+                uri: video.url.clone(),
+            };
+            let save_btn = Button::new(save_btn_state, Icon::PlaylistAdd.get_svg(theme))
+                .on_press(Message::AddToPlaylist(track.clone()))
+                .style(theme.tab_button_theme())
+                .width(Length::Units(30));
+            let btn = DragArea::new(btn)
+                .on_press(Message::Drag(DragMessage::Pressed(DragItem::Track(track))));
             let btn_row = Row::new()
                 .push(btn)
-                .push(Space::new(Length::Units(10), Length::Shrink));
+                .push(save_btn)
+                .push(Space::new(Length::Units(10), Length::Shrink))
+                .align_items(Align::Center);
 
             results_column = results_column.push(btn_row);
         }
 
-        let mut view = column.push(results_column).into();
-        if self.tooltip.1.elapsed() < TOOLTIP_DURATION && !self.tooltip.0.is_empty() {
-            view = Tooltip::new(
-                view,
-                format!("Added: {}", self.tooltip.0),
-                iced::tooltip::Position::Top,
-            )
-            .style(theme.tooltip_container_theme())
-            .gap(10)
-            .into()
-        }
-        view
+        column.push(results_column).into()
+    }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
     }
 }
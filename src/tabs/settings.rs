@@ -1,30 +1,293 @@
+use crate::bundle::{BundleMessage, ImportMode};
+use crate::config::{Config, ProfileEdit, Setting};
 use crate::icons::Icon;
 use crate::tabs::Tab;
 use crate::theme::Theme;
 use crate::Message;
-use iced::{Column, Command, Element, Length, Radio, Scrollable, Text};
+use iced::{
+    Button, Column, Command, Element, Length, Radio, Row, Scrollable, Space, Text, TextInput,
+};
+use log::LevelFilter;
+use reqwest::Url;
+use std::ops::RangeInclusive;
+
+const LOG_LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingField {
+    BotLink,
+    ClientId,
+    AuthUrl,
+    RedirectUrl,
+    DoubleClickInterval,
+    FontSize,
+    RewindStep,
+    ForwardStep,
+    SearchBackend,
+    ThumbnailCacheSize,
+}
+
+impl SettingField {
+    /// Ordered by section
+    pub const ALL: [SettingField; 10] = [
+        SettingField::BotLink,
+        SettingField::ClientId,
+        SettingField::AuthUrl,
+        SettingField::RedirectUrl,
+        SettingField::DoubleClickInterval,
+        SettingField::FontSize,
+        SettingField::RewindStep,
+        SettingField::ForwardStep,
+        SettingField::SearchBackend,
+        SettingField::ThumbnailCacheSize,
+    ];
+
+    fn section(&self) -> &'static str {
+        match self {
+            SettingField::BotLink
+            | SettingField::ClientId
+            | SettingField::AuthUrl
+            | SettingField::RedirectUrl => "Connection",
+            SettingField::DoubleClickInterval | SettingField::FontSize => "Interface",
+            SettingField::RewindStep | SettingField::ForwardStep => "Player",
+            SettingField::SearchBackend | SettingField::ThumbnailCacheSize => "Search",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingField::BotLink => "Bot Link",
+            SettingField::ClientId => "OAuth Client ID",
+            SettingField::AuthUrl => "OAuth Auth URL",
+            SettingField::RedirectUrl => "OAuth Redirect",
+            SettingField::DoubleClickInterval => "Double Click (ms)",
+            SettingField::FontSize => "Font Size",
+            SettingField::RewindStep => "Rewind Step (s)",
+            SettingField::ForwardStep => "Forward Step (s)",
+            SettingField::SearchBackend => "Search Backend",
+            SettingField::ThumbnailCacheSize => "Thumbnail Cache",
+        }
+    }
+
+    fn hint(&self) -> Option<&'static str> {
+        match self {
+            SettingField::BotLink
+            | SettingField::ClientId
+            | SettingField::AuthUrl
+            | SettingField::RedirectUrl => Some("Applies on reconnect"),
+            SettingField::FontSize => Some("Scales the whole window until restart"),
+            _ => None,
+        }
+    }
+
+    fn value(&self, cfg: &Config) -> String {
+        match self {
+            SettingField::BotLink => cfg.profile().bot_link.clone(),
+            SettingField::ClientId => cfg.profile().com.client_id.clone(),
+            SettingField::AuthUrl => cfg.profile().com.auth_url.clone(),
+            SettingField::RedirectUrl => cfg.profile().com.redirect_url.clone(),
+            SettingField::DoubleClickInterval => cfg.double_click_interval.to_string(),
+            SettingField::FontSize => cfg.font_size.to_string(),
+            SettingField::RewindStep => cfg.rewind_step.to_string(),
+            SettingField::ForwardStep => cfg.forward_step.to_string(),
+            SettingField::SearchBackend => cfg.search_backend.clone(),
+            SettingField::ThumbnailCacheSize => cfg.thumbnail_cache_size.to_string(),
+        }
+    }
+
+    /// Validates the input, returning the message shown below the field on failure
+    fn parse(&self, value: &str) -> Result<Setting, String> {
+        let value = value.trim();
+        match self {
+            SettingField::BotLink => parse_url(value, &["ws", "wss"]).map(Setting::BotLink),
+            SettingField::ClientId => {
+                if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(String::from("Client ID must be a number"));
+                }
+                Ok(Setting::ClientId(value.to_string()))
+            }
+            SettingField::AuthUrl => parse_url(value, &["http", "https"]).map(Setting::AuthUrl),
+            SettingField::RedirectUrl => {
+                let valid = value
+                    .rsplit_once(':')
+                    .map(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+                    .unwrap_or(false);
+                if !valid {
+                    return Err(String::from("Expected host:port, e.g. localhost:1887"));
+                }
+                Ok(Setting::RedirectUrl(value.to_string()))
+            }
+            SettingField::DoubleClickInterval => {
+                parse_number(value, 50..=2000).map(Setting::DoubleClickInterval)
+            }
+            SettingField::FontSize => parse_number(value, 8..=64).map(Setting::FontSize),
+            SettingField::RewindStep => parse_number(value, 1..=3600).map(Setting::RewindStep),
+            SettingField::ForwardStep => parse_number(value, 1..=3600).map(Setting::ForwardStep),
+            SettingField::SearchBackend => {
+                parse_url(value, &["http", "https"]).map(Setting::SearchBackend)
+            }
+            SettingField::ThumbnailCacheSize => {
+                parse_number(value, 1..=10000).map(Setting::ThumbnailCacheSize)
+            }
+        }
+    }
+}
+
+fn parse_url(value: &str, schemes: &[&str]) -> Result<String, String> {
+    let url = Url::parse(value).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(format!("URL must start with {}://", schemes.join(":// or ")));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_number<T>(value: &str, range: RangeInclusive<T>) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    match value.parse::<T>() {
+        Ok(n) if range.contains(&n) => Ok(n),
+        _ => Err(format!(
+            "Must be a number from {} to {}",
+            range.start(),
+            range.end()
+        )),
+    }
+}
 
 #[derive(Debug, Clone)]
-pub enum SettingsMessage {}
+pub enum SettingsMessage {
+    ConfigChanged(Box<Config>),
+    InputChanged(SettingField, String),
+    InputSubmitted(SettingField),
+    ProfileNameChanged(String),
+    LogLevelSelected(LevelFilter),
+    ImportPreview(Option<Vec<String>>),
+    BundleStatus(Option<String>),
+}
+
+#[derive(Debug, Default)]
+struct FieldState {
+    input: iced::text_input::State,
+    value: String,
+    /// Value in the config, the input differs from it until submitted
+    saved: String,
+    error: Option<String>,
+}
 
 #[derive(Debug)]
 pub struct SettingsTab {
     scroll: iced::scrollable::State,
+    profiles: Vec<String>,
+    active_profile: String,
+    profile_name: String,
+    profile_name_state: iced::text_input::State,
+    create_profile_button_state: iced::button::State,
+    rename_profile_button_state: iced::button::State,
+    delete_profile_button_state: iced::button::State,
+    log_level: LevelFilter,
+    fields: Vec<(SettingField, FieldState)>,
+    reconnect_button_state: iced::button::State,
+
+    import_preview: Option<Vec<String>>,
+    bundle_status: Option<String>,
+    export_button_state: iced::button::State,
+    import_button_state: iced::button::State,
+    merge_button_state: iced::button::State,
+    replace_button_state: iced::button::State,
+    cancel_button_state: iced::button::State,
 }
 
 impl SettingsTab {
     pub fn new() -> Self {
-        //TODO
         SettingsTab {
             scroll: Default::default(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
+            profile_name: String::new(),
+            profile_name_state: Default::default(),
+            create_profile_button_state: Default::default(),
+            rename_profile_button_state: Default::default(),
+            delete_profile_button_state: Default::default(),
+            log_level: LevelFilter::Info,
+            fields: SettingField::ALL
+                .iter()
+                .map(|f| (*f, FieldState::default()))
+                .collect(),
+            reconnect_button_state: Default::default(),
+            import_preview: None,
+            bundle_status: None,
+            export_button_state: Default::default(),
+            import_button_state: Default::default(),
+            merge_button_state: Default::default(),
+            replace_button_state: Default::default(),
+            cancel_button_state: Default::default(),
         }
     }
 
-    pub fn update(&mut self, _message: SettingsMessage) -> Command<Message> {
-        todo!()
+    pub fn update(&mut self, message: SettingsMessage) -> Command<Message> {
+        match message {
+            SettingsMessage::ConfigChanged(cfg) => {
+                self.profiles = cfg.profiles.keys().cloned().collect();
+                self.active_profile = cfg.active_profile.clone();
+                self.profile_name.clear();
+                self.log_level = cfg.log_level;
+                for (field, state) in self.fields.iter_mut() {
+                    state.value = field.value(&cfg);
+                    state.saved = state.value.clone();
+                    state.error = None;
+                }
+            }
+            SettingsMessage::InputChanged(field, value) => {
+                if let Some((_, state)) = self.fields.iter_mut().find(|(f, _)| field.eq(f)) {
+                    state.error = field.parse(&value).err();
+                    state.value = value;
+                }
+            }
+            SettingsMessage::InputSubmitted(field) => {
+                if let Some((_, state)) = self.fields.iter_mut().find(|(f, _)| field.eq(f)) {
+                    if state.value == state.saved {
+                        return Command::none();
+                    }
+                    match field.parse(&state.value) {
+                        Ok(setting) => {
+                            state.error = None;
+                            state.saved = state.value.clone();
+                            return setting_changed(setting);
+                        }
+                        Err(e) => state.error = Some(e),
+                    }
+                }
+            }
+            SettingsMessage::ProfileNameChanged(name) => self.profile_name = name,
+            SettingsMessage::LogLevelSelected(level) => {
+                self.log_level = level;
+                return setting_changed(Setting::LogLevel(level));
+            }
+            SettingsMessage::ImportPreview(preview) => {
+                if preview.is_some() {
+                    self.bundle_status = None;
+                }
+                self.import_preview = preview;
+            }
+            SettingsMessage::BundleStatus(status) => self.bundle_status = status,
+        }
+
+        Command::none()
     }
 }
 
+fn setting_changed(setting: Setting) -> Command<Message> {
+    Command::perform(futures::future::ready(setting), Message::SettingChanged)
+}
+
 impl Tab for SettingsTab {
     type Message = Message;
 
@@ -37,7 +300,6 @@ impl Tab for SettingsTab {
     }
 
     fn content(&mut self, theme: &Theme) -> Element<'_, Self::Message> {
-        //TODO
         let mut column = Column::new()
             .spacing(10)
             .push(Text::new("Theme").size(26).color(theme.text_color()));
@@ -49,10 +311,170 @@ impl Tab for SettingsTab {
             );
         }
 
+        column = column.push(Text::new("Profile").size(26).color(theme.text_color()));
+        let selected = self.profiles.iter().position(|p| p.eq(&self.active_profile));
+        for (i, profile) in self.profiles.iter().enumerate() {
+            let name = profile.clone();
+            column = column.push(
+                Radio::new(i, profile, selected, move |_| {
+                    Message::ProfileSelected(name.clone())
+                })
+                .style(theme.radio_button_theme()),
+            );
+        }
+        let name = self.profile_name.trim().to_string();
+        let mut create = Button::new(&mut self.create_profile_button_state, Text::new("New"))
+            .style(theme.tab_button_theme());
+        let mut rename = Button::new(&mut self.rename_profile_button_state, Text::new("Rename"))
+            .style(theme.tab_button_theme());
+        if !name.is_empty() {
+            create = create.on_press(Message::ProfileEdited(ProfileEdit::Create(name.clone())));
+            rename = rename.on_press(Message::ProfileEdited(ProfileEdit::Rename(
+                self.active_profile.clone(),
+                name,
+            )));
+        }
+        let mut delete = Button::new(&mut self.delete_profile_button_state, Text::new("Delete"))
+            .style(theme.tab_button_theme());
+        if self.profiles.len() > 1 {
+            delete = delete.on_press(Message::ProfileEdited(ProfileEdit::Delete(
+                self.active_profile.clone(),
+            )));
+        }
+        column = column
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        TextInput::new(
+                            &mut self.profile_name_state,
+                            "Profile name",
+                            &self.profile_name,
+                            |v| Message::Settings(SettingsMessage::ProfileNameChanged(v)),
+                        )
+                        .padding(5)
+                        .width(Length::Units(220))
+                        .style(theme.search_input_theme()),
+                    )
+                    .push(create)
+                    .push(rename)
+                    .push(delete),
+            )
+            .push(
+                Text::new("Rename and Delete apply to the selected profile")
+                    .size(14)
+                    .color(theme.text_color()),
+            )
+            .push(
+                Button::new(&mut self.reconnect_button_state, Text::new("Reconnect"))
+                    .on_press(Message::Reconnect())
+                    .style(theme.tab_button_theme()),
+            );
+
+        let mut section = "";
+        for (field, state) in self.fields.iter_mut() {
+            if field.section() != section {
+                section = field.section();
+                column = column.push(Text::new(section).size(26).color(theme.text_color()));
+            }
+            let field = *field;
+            let input = TextInput::new(&mut state.input, "", &state.value, move |v| {
+                Message::Settings(SettingsMessage::InputChanged(field, v))
+            })
+            .on_submit(Message::Settings(SettingsMessage::InputSubmitted(field)))
+            .padding(5)
+            .style(theme.search_input_theme());
+            let row = Row::new()
+                .push(
+                    Text::new(field.label())
+                        .color(theme.text_color())
+                        .width(Length::Units(220)),
+                )
+                .push(input)
+                .push(Space::new(Length::Units(15), Length::Shrink));
+            column = column.push(row);
+
+            let note = match (state.error.as_ref(), field.hint()) {
+                (Some(e), _) => Some(Text::new(e.as_str()).color(theme.error_color())),
+                (None, _) if state.value != state.saved => {
+                    Some(Text::new("Press Enter to apply").color(theme.text_color()))
+                }
+                (None, Some(hint)) => Some(Text::new(hint).color(theme.text_color())),
+                (None, None) => None,
+            };
+            if let Some(note) = note {
+                column = column.push(
+                    Row::new()
+                        .push(Space::new(Length::Units(220), Length::Shrink))
+                        .push(note.size(14)),
+                );
+            }
+        }
+
+        column = column.push(Text::new("Log Level").size(26).color(theme.text_color()));
+        for level in LOG_LEVELS.iter() {
+            column = column.push(
+                Radio::new(*level, &level.to_string(), Some(self.log_level), |l| {
+                    Message::Settings(SettingsMessage::LogLevelSelected(l))
+                })
+                .style(theme.radio_button_theme()),
+            );
+        }
+
+        column = column
+            .push(Text::new("Settings Bundle").size(26).color(theme.text_color()))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(&mut self.export_button_state, Text::new("Export"))
+                            .on_press(Message::Bundle(BundleMessage::Export()))
+                            .style(theme.tab_button_theme()),
+                    )
+                    .push(
+                        Button::new(&mut self.import_button_state, Text::new("Import"))
+                            .on_press(Message::Bundle(BundleMessage::Import()))
+                            .style(theme.tab_button_theme()),
+                    ),
+            );
+        if let Some(status) = self.bundle_status.as_ref() {
+            column = column.push(Text::new(status.as_str()).size(14).color(theme.text_color()));
+        }
+        if let Some(preview) = self.import_preview.as_ref() {
+            for line in preview.iter() {
+                column = column.push(Text::new(line.as_str()).size(16).color(theme.text_color()));
+            }
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(&mut self.merge_button_state, Text::new("Merge"))
+                            .on_press(Message::Bundle(BundleMessage::Apply(ImportMode::Merge)))
+                            .style(theme.tab_button_theme()),
+                    )
+                    .push(
+                        Button::new(&mut self.replace_button_state, Text::new("Replace"))
+                            .on_press(Message::Bundle(BundleMessage::Apply(
+                                ImportMode::Replace,
+                            )))
+                            .style(theme.tab_button_theme()),
+                    )
+                    .push(
+                        Button::new(&mut self.cancel_button_state, Text::new("Cancel"))
+                            .on_press(Message::Bundle(BundleMessage::Cancel()))
+                            .style(theme.tab_button_theme()),
+                    ),
+            );
+        }
+
         Scrollable::new(&mut self.scroll)
             .push(column)
             .width(Length::Fill)
             .height(Length::Fill)
             .into()
     }
+
+    fn scroll_state(&mut self) -> &mut iced::scrollable::State {
+        &mut self.scroll
+    }
 }
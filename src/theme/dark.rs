@@ -1,4 +1,5 @@
-use iced::{button, container, radio, slider, text_input, Background, Color, Vector};
+use iced::{button, container, pick_list, radio, slider, text_input, Background, Color, Vector};
+use iced_style::menu;
 
 pub const TEXT: Color = Color::from_rgb(
     0xD2 as f32 / 255.0,
@@ -6,6 +7,12 @@ pub const TEXT: Color = Color::from_rgb(
     0xD5 as f32 / 255.0,
 );
 
+pub const ERROR: Color = Color::from_rgb(
+    0xF0 as f32 / 255.0,
+    0x47 as f32 / 255.0,
+    0x47 as f32 / 255.0,
+);
+
 const HOVERED_TEXT: Color = Color::from_rgb(
     0xDC as f32 / 255.0,
     0xDD as f32 / 255.0,
@@ -44,7 +51,7 @@ const SURFACE_3: Color = Color::from_rgb(
 );
  */
 
-const ACCENT: Color = Color::from_rgb(
+pub const ACCENT: Color = Color::from_rgb(
     0x72 as f32 / 255.0,
     0x89 as f32 / 255.0,
     0xDA as f32 / 255.0,
@@ -144,6 +151,77 @@ impl container::StyleSheet for TooltipContainer {
     }
 }
 
+pub struct LiveBadge;
+
+impl container::StyleSheet for LiveBadge {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: Color::WHITE.into(),
+            background: ERROR.into(),
+            border_radius: 4.0,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct SeekPreview;
+
+impl container::StyleSheet for SeekPreview {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: TEXT.into(),
+            background: SURFACE_2.into(),
+            border_radius: 5.0,
+            border_width: 1.0,
+            border_color: SURFACE_3,
+        }
+    }
+}
+
+pub struct PickList;
+
+impl pick_list::StyleSheet for PickList {
+    fn menu(&self) -> menu::Style {
+        menu::Style {
+            text_color: TEXT,
+            background: SURFACE_1.into(),
+            border_width: 0.0,
+            border_color: SURFACE_3,
+            selected_text_color: TEXT,
+            selected_background: SURFACE_3.into(),
+        }
+    }
+
+    fn active(&self) -> pick_list::Style {
+        pick_list::Style {
+            text_color: TEXT,
+            background: SURFACE_1.into(),
+            border_radius: 5.0,
+            border_width: 0.0,
+            border_color: SURFACE_3,
+            icon_size: 0.5,
+        }
+    }
+
+    fn hovered(&self) -> pick_list::Style {
+        pick_list::Style {
+            background: SURFACE_2.into(),
+            ..self.active()
+        }
+    }
+}
+
+pub struct DropIndicator;
+
+impl container::StyleSheet for DropIndicator {
+    fn style(&self) -> container::Style {
+        container::Style {
+            background: ACCENT.into(),
+            ..Default::default()
+        }
+    }
+}
+
 pub struct TabsContainer;
 
 impl container::StyleSheet for TabsContainer {
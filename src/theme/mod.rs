@@ -1,8 +1,9 @@
 mod dark;
 mod light;
 
-use iced::{button, container, radio, slider, text_input, Color};
+use iced::{button, container, pick_list, radio, slider, text_input, Color};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Theme {
@@ -20,6 +21,34 @@ impl Theme {
         }
     }
 
+    pub fn live_badge_theme(&self) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Light => light::LiveBadge.into(),
+            Theme::Dark => dark::LiveBadge.into(),
+        }
+    }
+
+    pub fn seek_preview_theme(&self) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Light => light::SeekPreview.into(),
+            Theme::Dark => dark::SeekPreview.into(),
+        }
+    }
+
+    pub fn drop_indicator_theme(&self) -> Box<dyn container::StyleSheet> {
+        match self {
+            Theme::Light => light::DropIndicator.into(),
+            Theme::Dark => dark::DropIndicator.into(),
+        }
+    }
+
+    pub fn pick_list_theme(&self) -> Box<dyn pick_list::StyleSheet> {
+        match self {
+            Theme::Light => light::PickList.into(),
+            Theme::Dark => dark::PickList.into(),
+        }
+    }
+
     pub fn radio_button_theme(&self) -> Box<dyn radio::StyleSheet> {
         match self {
             Theme::Light => light::RadioButton.into(),
@@ -34,6 +63,20 @@ impl Theme {
         }
     }
 
+    pub fn accent_color(&self) -> Color {
+        match self {
+            Theme::Light => light::ACCENT,
+            Theme::Dark => dark::ACCENT,
+        }
+    }
+
+    pub fn error_color(&self) -> Color {
+        match self {
+            Theme::Light => light::ERROR,
+            Theme::Dark => dark::ERROR,
+        }
+    }
+
     pub fn tab_view_container_theme(&self) -> Box<dyn container::StyleSheet> {
         match self {
             Theme::Light => light::TabViewContainer.into(),
@@ -104,6 +147,18 @@ impl Default for Theme {
     }
 }
 
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::ALL
+            .iter()
+            .find(|t| format!("{:?}", t).eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown Theme: {}", s))
+    }
+}
+
 /*
 impl From<Theme> for Box<dyn container::StyleSheet> {
     fn from(theme: Theme) -> Self {
@@ -0,0 +1,149 @@
+use crate::theme::Theme;
+use crate::Message;
+use iced::{Button, Column, Command, Container, Element, Length, Row, Text};
+use std::time::Duration;
+
+/// Toasts shown at once, older ones are dropped
+const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+}
+
+/// A short notice which disappears on its own
+#[derive(Debug, Clone)]
+pub struct Toast {
+    text: String,
+    severity: Severity,
+    duration: Duration,
+    /// Sent when the toast is clicked
+    action: Option<Box<Message>>,
+}
+
+impl Toast {
+    pub fn new(text: String, severity: Severity, duration: Duration) -> Self {
+        Toast {
+            text,
+            severity,
+            duration,
+            action: None,
+        }
+    }
+
+    pub fn info(text: String) -> Self {
+        Toast::new(text, Severity::Info, Duration::from_secs(3))
+    }
+
+    pub fn success(text: String) -> Self {
+        Toast::new(text, Severity::Success, Duration::from_secs(2))
+    }
+
+    pub fn warning(text: String) -> Self {
+        Toast::new(text, Severity::Warning, Duration::from_secs(5))
+    }
+
+    pub fn on_click(mut self, action: Message) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+}
+
+/// Command showing the toast, usable from every component
+pub fn show(toast: Toast) -> Command<Message> {
+    Command::perform(futures::future::ready(toast), Message::ShowToast)
+}
+
+#[derive(Debug, Clone)]
+pub enum ToastMessage {
+    Expired(usize),
+    Clicked(usize),
+}
+
+#[derive(Debug)]
+struct ShownToast {
+    id: usize,
+    toast: Toast,
+    button_state: iced::button::State,
+}
+
+/// Stack of the currently shown toasts, newest at the bottom
+#[derive(Debug)]
+pub struct Toasts {
+    toasts: Vec<ShownToast>,
+    next_id: usize,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Toasts {
+            toasts: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn push(&mut self, toast: Toast) -> Command<Message> {
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        let duration = toast.duration;
+        self.toasts.push(ShownToast {
+            id,
+            toast,
+            button_state: Default::default(),
+        });
+        Command::perform(tokio::time::sleep(duration), move |_| {
+            Message::Toast(ToastMessage::Expired(id))
+        })
+    }
+
+    /// Handles the message, returning the action of a clicked toast
+    pub fn update(&mut self, message: ToastMessage) -> Option<Message> {
+        match message {
+            ToastMessage::Expired(id) => {
+                self.toasts.retain(|t| t.id != id);
+                None
+            }
+            ToastMessage::Clicked(id) => {
+                let index = self.toasts.iter().position(|t| t.id == id)?;
+                self.toasts.remove(index).toast.action.map(|a| *a)
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    pub fn view(&mut self, theme: &Theme) -> Element<'_, Message> {
+        let mut column = Column::new().spacing(5).width(Length::Fill);
+        for shown in self.toasts.iter_mut() {
+            let color = match shown.toast.severity {
+                Severity::Warning => theme.error_color(),
+                Severity::Info | Severity::Success => theme.text_color(),
+            };
+            let content = Container::new(
+                Row::new().push(
+                    Text::new(shown.toast.text.as_str())
+                        .size(16)
+                        .color(color)
+                        .width(Length::Fill),
+                ),
+            )
+            .width(Length::Fill)
+            .padding(5)
+            .style(theme.tooltip_container_theme());
+            //Clicking dismisses the toast, even without an action
+            let button = Button::new(&mut shown.button_state, content)
+                .on_press(Message::Toast(ToastMessage::Clicked(shown.id)))
+                .style(theme.tab_button_theme())
+                .width(Length::Fill);
+            column = column.push(button);
+        }
+        column.into()
+    }
+}
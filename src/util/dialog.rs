@@ -0,0 +1,45 @@
+use rfd::AsyncFileDialog;
+use std::path::PathBuf;
+
+/// Asks the user where to save a file, `None` if the dialog was cancelled
+pub async fn save_path(
+    filter: &'static str,
+    extensions: &'static [&'static str],
+    file_name: String,
+) -> Option<PathBuf> {
+    AsyncFileDialog::new()
+        .add_filter(filter, extensions)
+        .set_file_name(&file_name)
+        .save_file()
+        .await
+        .map(|f| f.path().to_path_buf())
+}
+
+/// Asks the user where to save a file which can be written in several formats,
+/// told apart by the extension of the path
+pub async fn save_path_filters(
+    filters: &'static [(&'static str, &'static [&'static str])],
+    file_name: String,
+) -> Option<PathBuf> {
+    let mut dialog = AsyncFileDialog::new();
+    for (filter, extensions) in filters.iter() {
+        dialog = dialog.add_filter(filter, extensions);
+    }
+    dialog
+        .set_file_name(&file_name)
+        .save_file()
+        .await
+        .map(|f| f.path().to_path_buf())
+}
+
+/// Asks the user which file to open, `None` if the dialog was cancelled
+pub async fn open_path(
+    filter: &'static str,
+    extensions: &'static [&'static str],
+) -> Option<PathBuf> {
+    AsyncFileDialog::new()
+        .add_filter(filter, extensions)
+        .pick_file()
+        .await
+        .map(|f| f.path().to_path_buf())
+}
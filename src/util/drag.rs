@@ -0,0 +1,143 @@
+use iced_native::event::{self, Event};
+use iced_native::layout::{self, Layout};
+use iced_native::mouse;
+use iced_native::overlay;
+use iced_native::{Clipboard, Element, Hasher, Length, Point, Rectangle, Widget};
+use std::hash::Hash;
+
+/// Makes the content a source or a target of drag and drop, the drag itself is tracked by the app
+pub struct DragArea<'a, Message, Renderer> {
+    content: Element<'a, Message, Renderer>,
+    on_press: Option<Message>,
+    /// Gets the position of the cursor as a fraction of the height, and the height
+    on_hover: Option<Box<dyn Fn(f32, f32) -> Message + 'a>>,
+    on_leave: Option<Message>,
+}
+
+impl<'a, Message, Renderer> DragArea<'a, Message, Renderer> {
+    pub fn new<E>(content: E) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+    {
+        DragArea {
+            content: content.into(),
+            on_press: None,
+            on_hover: None,
+            on_leave: None,
+        }
+    }
+
+    /// Sent when the left button is pressed over the content, which might start a drag
+    pub fn on_press(mut self, message: Message) -> Self {
+        self.on_press = Some(message);
+        self
+    }
+
+    /// Sent on every cursor movement over the content, so only to be set while something is dragged
+    pub fn on_hover<F>(mut self, on_hover: F) -> Self
+    where
+        F: 'a + Fn(f32, f32) -> Message,
+    {
+        self.on_hover = Some(Box::new(on_hover));
+        self
+    }
+
+    /// Sent on every cursor movement outside of the content
+    pub fn on_leave(mut self, message: Message) -> Self {
+        self.on_leave = Some(message);
+        self
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for DragArea<'a, Message, Renderer>
+where
+    Message: Clone,
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        let status = self.content.on_event(
+            event.clone(),
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        );
+        //Rows are usually buttons, which capture the press but still have to start the drag
+        let bounds = layout.bounds();
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if bounds.contains(cursor_position) =>
+            {
+                if let Some(message) = self.on_press.as_ref() {
+                    messages.push(message.clone());
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if bounds.contains(cursor_position) => {
+                if let Some(on_hover) = self.on_hover.as_ref() {
+                    let fraction = (cursor_position.y - bounds.y) / bounds.height;
+                    messages.push(on_hover(fraction, bounds.height));
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let Some(message) = self.on_leave.as_ref() {
+                    messages.push(message.clone());
+                }
+            }
+            _ => {}
+        }
+        status
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+impl<'a, Message, Renderer> From<DragArea<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + iced_native::Renderer,
+    Message: 'a + Clone,
+{
+    fn from(area: DragArea<'a, Message, Renderer>) -> Self {
+        Element::new(area)
+    }
+}
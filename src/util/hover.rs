@@ -0,0 +1,185 @@
+use iced_native::event::{self, Event};
+use iced_native::layout::{self, Layout};
+use iced_native::mouse;
+use iced_native::overlay::{self, Overlay};
+use iced_native::{Clipboard, Element, Hasher, Length, Point, Rectangle, Size, Widget};
+use std::hash::Hash;
+
+/// Space between the preview and the hovered content
+const PREVIEW_GAP: f32 = 8.0;
+
+/// Reports where the cursor hovers over the content, as a fraction of its width,
+/// and shows a preview above that point
+pub struct HoverArea<'a, Message, Renderer> {
+    content: Element<'a, Message, Renderer>,
+    hovered: Option<f32>,
+    on_hover: Box<dyn Fn(Option<f32>) -> Message + 'a>,
+    preview: Option<Element<'a, Message, Renderer>>,
+}
+
+impl<'a, Message, Renderer> HoverArea<'a, Message, Renderer> {
+    /// `hovered` is the fraction last reported, so only changes are sent
+    pub fn new<E, F>(content: E, hovered: Option<f32>, on_hover: F) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+        F: 'a + Fn(Option<f32>) -> Message,
+    {
+        HoverArea {
+            content: content.into(),
+            hovered,
+            on_hover: Box::new(on_hover),
+            preview: None,
+        }
+    }
+
+    /// Shown above the cursor while hovering
+    pub fn preview<E>(mut self, preview: E) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+    {
+        self.preview = Some(preview.into());
+        self
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for HoverArea<'a, Message, Renderer>
+where
+    Message: 'a,
+    Renderer: 'a + iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        let status = self.content.on_event(
+            event.clone(),
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        );
+        //The hover is tracked even while the content drags, so the preview follows the slider
+        let bounds = layout.bounds();
+        let hovered = match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if bounds.contains(cursor_position) => {
+                Some(
+                    ((cursor_position.x - bounds.x) / bounds.width)
+                        .max(0.0)
+                        .min(1.0),
+                )
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. })
+            | Event::Mouse(mouse::Event::CursorLeft) => None,
+            _ => return status,
+        };
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            messages.push((self.on_hover)(hovered));
+        }
+        status
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        let bounds = layout.bounds();
+        match (self.hovered, self.preview.as_ref()) {
+            (Some(fraction), Some(content)) => Some(overlay::Element::new(
+                Point::new(bounds.x + fraction * bounds.width, bounds.y),
+                Box::new(Preview { content }),
+            )),
+            _ => self.content.overlay(layout),
+        }
+    }
+}
+
+impl<'a, Message, Renderer> From<HoverArea<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + iced_native::Renderer,
+    Message: 'a,
+{
+    fn from(area: HoverArea<'a, Message, Renderer>) -> Self {
+        Element::new(area)
+    }
+}
+
+/// The preview, centered above the hovered point and kept inside the window
+struct Preview<'b, 'a, Message, Renderer> {
+    content: &'b Element<'a, Message, Renderer>,
+}
+
+impl<'b, 'a, Message, Renderer> Overlay<Message, Renderer> for Preview<'b, 'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn layout(&self, renderer: &Renderer, bounds: Size, position: Point) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, bounds);
+        let mut node = self.content.layout(renderer, &limits);
+        let size = node.size();
+        let x = (position.x - size.width / 2.0)
+            .min(bounds.width - size.width)
+            .max(0.0);
+        let y = (position.y - size.height - PREVIEW_GAP).max(0.0);
+        node.move_to(Point::new(x, y));
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> Renderer::Output {
+        self.content.draw(
+            renderer,
+            defaults,
+            layout,
+            cursor_position,
+            &layout.bounds(),
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher, position: Point) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        (position.x as u32).hash(state);
+        (position.y as u32).hash(state);
+        self.content.hash_layout(state);
+    }
+}
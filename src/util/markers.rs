@@ -0,0 +1,146 @@
+use iced_graphics::{Backend, Primitive, Renderer};
+use iced_native::event::{self, Event};
+use iced_native::layout::{self, Layout};
+use iced_native::overlay;
+use iced_native::{
+    Background, Clipboard, Color, Element, Hasher, Length, Point, Rectangle, Widget,
+};
+use std::hash::Hash;
+
+const MARKER_WIDTH: f32 = 2.0;
+/// Height of the markers relative to the content
+const MARKER_HEIGHT: f32 = 0.6;
+
+/// Draws markers over the content at fractions of its width, e.g. the loop points of a slider
+pub struct Markers<'a, Message, B: Backend> {
+    content: Element<'a, Message, Renderer<B>>,
+    markers: Vec<f32>,
+    color: Color,
+    /// Shades the space between the first two markers
+    shade_range: bool,
+}
+
+impl<'a, Message, B: Backend> Markers<'a, Message, B> {
+    pub fn new<E>(content: E, markers: Vec<f32>, color: Color) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer<B>>>,
+    {
+        Markers {
+            content: content.into(),
+            markers,
+            color,
+            shade_range: false,
+        }
+    }
+
+    pub fn shade_range(mut self, shade: bool) -> Self {
+        self.shade_range = shade;
+        self
+    }
+}
+
+impl<'a, Message, B> Widget<Message, Renderer<B>> for Markers<'a, Message, B>
+where
+    B: Backend,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer<B>, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer<B>,
+        defaults: &iced_graphics::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> (Primitive, iced_native::mouse::Interaction) {
+        let (content, interaction) =
+            self.content
+                .draw(renderer, defaults, layout, cursor_position, viewport);
+        let bounds = layout.bounds();
+        let height = bounds.height * MARKER_HEIGHT;
+        let y = bounds.y + (bounds.height - height) / 2.0;
+        let x = |fraction: f32| bounds.x + fraction.max(0.0).min(1.0) * bounds.width;
+        let quad = |x: f32, width: f32, color: Color| Primitive::Quad {
+            bounds: Rectangle {
+                x,
+                y,
+                width,
+                height,
+            },
+            background: Background::Color(color),
+            border_radius: 0.0,
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        };
+
+        let mut primitives = Vec::with_capacity(self.markers.len() + 2);
+        if let (true, [start, end, ..]) = (self.shade_range, self.markers.as_slice()) {
+            let shade = Color {
+                a: 0.2,
+                ..self.color
+            };
+            primitives.push(quad(x(*start), x(*end) - x(*start), shade));
+        }
+        primitives.push(content);
+        for marker in self.markers.iter() {
+            primitives.push(quad(
+                x(*marker) - MARKER_WIDTH / 2.0,
+                MARKER_WIDTH,
+                self.color,
+            ));
+        }
+        (Primitive::Group { primitives }, interaction)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer<B>,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        self.content.on_event(
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        )
+    }
+
+    fn overlay(
+        &mut self,
+        layout: Layout<'_>,
+    ) -> Option<overlay::Element<'_, Message, Renderer<B>>> {
+        self.content.overlay(layout)
+    }
+}
+
+impl<'a, Message, B> From<Markers<'a, Message, B>> for Element<'a, Message, Renderer<B>>
+where
+    B: 'a + Backend,
+    Message: 'a,
+{
+    fn from(markers: Markers<'a, Message, B>) -> Self {
+        Element::new(markers)
+    }
+}
@@ -1,13 +1,50 @@
-use cached::proc_macro::cached;
+use crate::config::DEFAULT_THUMBNAIL_CACHE_SIZE;
+use crate::error::Error;
+use cached::{Cached, SizedCache};
+use once_cell::sync::Lazy;
 use reciprocity_communication::messages::oauth2::url::ParseError;
 use reciprocity_communication::messages::Track;
 use reqwest::Url;
 use std::borrow::Cow;
 use std::fmt::Write;
-use std::time::{Duration, Instant};
+use std::sync::Mutex;
+use std::time::Duration;
 
+pub mod dialog;
+pub mod drag;
+pub mod hover;
+pub mod markers;
+pub mod storyboard;
+pub mod wheel;
 pub mod youtube;
 
+/// Bounds for reading and writing the raw offset of a scrollable::State.
+/// The state only exposes its offset relative to layout bounds, so an empty viewport
+/// over infinitely high content leaves the offset untouched.
+const SCROLL_BOUNDS: (iced::Rectangle, iced::Rectangle) = (
+    iced::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 0.0,
+        height: 0.0,
+    },
+    iced::Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 0.0,
+        height: f32::MAX,
+    },
+);
+
+pub fn scroll_offset(state: &iced::scrollable::State) -> f32 {
+    state.offset(SCROLL_BOUNDS.0, SCROLL_BOUNDS.1) as f32
+}
+
+pub fn set_scroll_offset(state: &mut iced::scrollable::State, offset: f32) {
+    *state = Default::default();
+    state.scroll(-offset, SCROLL_BOUNDS.0, SCROLL_BOUNDS.1);
+}
+
 pub fn duration_fmt(dur: &'_ Duration) -> String {
     let seconds = dur.as_secs() % 60;
     let minutes = (dur.as_secs() / 60) % 60;
@@ -20,20 +57,46 @@ pub fn duration_fmt(dur: &'_ Duration) -> String {
     msg
 }
 
+/// Streams report no length or the largest one possible
+pub fn is_live(track: &Track) -> bool {
+    track.len.is_zero() || track.len.as_secs() >= u64::from(u32::MAX)
+}
+
+/// Length of the track, or "live" for streams
+pub fn track_len_fmt(track: &Track) -> String {
+    match is_live(track) {
+        true => String::from("live"),
+        false => duration_fmt(&track.len),
+    }
+}
+
+/// Parses `ss`, `mm:ss` or `hh:mm:ss`, the reverse of [`duration_fmt`]
+pub fn duration_parse(input: &str) -> Option<Duration> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut secs = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u64 = part.parse().ok()?;
+        //Only the leading part may exceed its unit
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        secs = secs * 60 + value;
+    }
+    Some(Duration::from_secs(secs))
+}
+
 #[derive(Debug, Clone)]
 pub struct CompTrack {
     pub track: Track,
-    pub updated: Instant,
     pub image: Option<iced::image::Handle>,
 }
 
 impl CompTrack {
     pub fn new(track: Track) -> Self {
-        CompTrack {
-            track,
-            updated: Instant::now(),
-            image: None,
-        }
+        CompTrack { track, image: None }
     }
 
     pub fn pos(&self, pos: f32) -> Duration {
@@ -42,22 +105,23 @@ impl CompTrack {
         Duration::from_millis((factor * millis) as u64)
     }
 
-    pub fn pos_percentage(&self) -> f32 {
-        let elapsed = self.updated.elapsed().as_millis() as f32;
-        let track_pos = self.track.pos.as_millis() as f32;
-        let track_len = self.track.len.as_millis() as f32;
+    pub fn is_live(&self) -> bool {
+        is_live(&self.track)
+    }
 
-        let percentage = ((elapsed + track_pos) / track_len) * 100.0;
-        if percentage > 100.0 {
-            return 100.0;
+    /// Reverse of [`CompTrack::pos`]
+    pub fn percentage(&self, pos: Duration) -> f32 {
+        if self.track.len.is_zero() {
+            return 0.0;
         }
-        percentage
+        let percentage = pos.as_secs_f32() / self.track.len.as_secs_f32() * 100.0;
+        percentage.min(100.0)
     }
 
-    pub async fn download_image(mut self) -> Result<Self, String> {
+    pub async fn download_image(mut self) -> Result<Self, Error> {
         let url = self.track.uri.clone();
-        let id = get_yt_identifier(url).map_err(|e| format!("{:?}", e))?;
-        let img_url = get_image_uri_from_yt_id(id).map_err(|e| format!("{:?}", e))?;
+        let id = get_yt_identifier(url.clone()).map_err(|_| Error::InvalidUrl(url.clone()))?;
+        let img_url = get_image_uri_from_yt_id(id).map_err(|_| Error::InvalidUrl(url))?;
         let img = get_image(img_url).await?;
         self.image = Some(img);
 
@@ -65,15 +129,32 @@ impl CompTrack {
     }
 }
 
-#[cached(size = 100)]
-pub async fn get_image(url: Url) -> Result<iced::image::Handle, String> {
-    let bytes = reqwest::get(url)
+static IMAGE_CACHE: Lazy<Mutex<SizedCache<Url, iced::image::Handle>>> =
+    Lazy::new(|| Mutex::new(SizedCache::with_size(DEFAULT_THUMBNAIL_CACHE_SIZE)));
+
+/// Replaces the image cache with an empty one of the given size, if the size changed
+pub fn set_image_cache_size(size: usize) {
+    //SizedCache panics for a size of 0
+    let size = size.max(1);
+    let mut cache = IMAGE_CACHE.lock().unwrap();
+    if cache.cache_capacity() != Some(size) {
+        *cache = SizedCache::with_size(size);
+    }
+}
+
+pub async fn get_image(url: Url) -> Result<iced::image::Handle, Error> {
+    if let Some(img) = IMAGE_CACHE.lock().unwrap().cache_get(&url) {
+        return Ok(img.clone());
+    }
+    let bytes = reqwest::get(url.clone())
         .await
-        .map_err(|e| format!("{:?}", e))?
+        .map_err(|e| Error::Image(url.to_string(), e.to_string()))?
         .bytes()
         .await
-        .map_err(|e| format!("{:?}", e))?;
-    Ok(iced::image::Handle::from_memory(bytes.to_vec()))
+        .map_err(|e| Error::Image(url.to_string(), e.to_string()))?;
+    let img = iced::image::Handle::from_memory(bytes.to_vec());
+    IMAGE_CACHE.lock().unwrap().cache_set(url, img.clone());
+    Ok(img)
 }
 
 pub fn get_yt_identifier(url: String) -> Result<String, Option<ParseError>> {
@@ -0,0 +1,134 @@
+use crate::error::Error;
+use cached::{Cached, SizedCache};
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Where the watch page keeps the storyboard spec
+const SPEC_MARKER: &str = "\"playerStoryboardSpecRenderer\":{\"spec\":\"";
+/// Sheets kept decoded, one covers several minutes of a video
+const SHEET_CACHE_SIZE: usize = 4;
+
+static SHEET_CACHE: Lazy<Mutex<SizedCache<String, Arc<image::DynamicImage>>>> =
+    Lazy::new(|| Mutex::new(SizedCache::with_size(SHEET_CACHE_SIZE)));
+
+/// Preview thumbnails of a YouTube video, packed into sheets of `cols` x `rows` frames
+#[derive(Debug, Clone)]
+pub struct Storyboard {
+    /// Url of the sheets, with `$M` in place of the sheet number
+    url: String,
+    width: u32,
+    height: u32,
+    count: u32,
+    cols: u32,
+    rows: u32,
+    /// Time between two frames
+    interval: Duration,
+}
+
+impl Storyboard {
+    /// Looks up the storyboard on the watch page of the video
+    pub async fn fetch(id: String, len: Duration) -> Result<Self, Error> {
+        let page_url = format!("https://www.youtube.com/watch?v={}", id);
+        let page = reqwest::get(page_url.as_str())
+            .await
+            .map_err(|e| Error::Image(page_url.clone(), e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| Error::Image(page_url.clone(), e.to_string()))?;
+        let spec = page
+            .split(SPEC_MARKER)
+            .nth(1)
+            .and_then(|s| s.split('"').next())
+            .ok_or_else(|| Error::Image(page_url.clone(), String::from("No storyboard")))?;
+        Storyboard::parse(&spec.replace("\\u0026", "&"), len)
+            .ok_or_else(|| Error::Image(page_url, format!("Invalid storyboard: {}", spec)))
+    }
+
+    /// Parses `url|level|level...` with levels of `width#height#count#cols#rows#interval#name#sigh`,
+    /// taking the level with the largest frames
+    fn parse(spec: &str, len: Duration) -> Option<Self> {
+        let mut parts = spec.split('|');
+        let base = parts.next()?;
+        let (level, fields) = parts.enumerate().last()?;
+        let fields: Vec<&str> = fields.split('#').collect();
+        if fields.len() < 8 {
+            return None;
+        }
+        let number = |i: usize| fields[i].parse::<u32>().ok().filter(|n| *n > 0);
+        let count = number(2)?;
+        //Levels without an interval spread their frames over the whole video
+        let interval = match fields[5].parse::<u64>().ok()? {
+            0 => len / count,
+            ms => Duration::from_millis(ms),
+        };
+        Some(Storyboard {
+            url: format!(
+                "{}&sigh={}",
+                base.replace("$L", &level.to_string())
+                    .replace("$N", fields[6]),
+                fields[7]
+            ),
+            width: number(0)?,
+            height: number(1)?,
+            count,
+            cols: number(3)?,
+            rows: number(4)?,
+            interval,
+        })
+    }
+
+    /// Number of the frame shown at the position
+    pub fn frame(&self, pos: Duration) -> u32 {
+        if self.interval.is_zero() {
+            return 0;
+        }
+        let frame = pos.as_millis() / self.interval.as_millis();
+        frame.min(u128::from(self.count - 1)) as u32
+    }
+
+    /// Cuts the frame out of its sheet
+    pub async fn thumbnail(self, frame: u32) -> Result<iced::image::Handle, Error> {
+        let per_sheet = self.cols * self.rows;
+        let url = self.url.replace("$M", &(frame / per_sheet).to_string());
+        let sheet = load_sheet(url.clone()).await?;
+        let index = frame % per_sheet;
+        tokio::task::spawn_blocking(move || {
+            let thumbnail = sheet
+                .crop_imm(
+                    index % self.cols * self.width,
+                    index / self.cols * self.height,
+                    self.width,
+                    self.height,
+                )
+                .to_bgra8();
+            iced::image::Handle::from_pixels(
+                thumbnail.width(),
+                thumbnail.height(),
+                thumbnail.into_raw(),
+            )
+        })
+        .await
+        .map_err(|e| Error::Image(url, e.to_string()))
+    }
+}
+
+async fn load_sheet(url: String) -> Result<Arc<image::DynamicImage>, Error> {
+    if let Some(sheet) = SHEET_CACHE.lock().unwrap().cache_get(&url) {
+        return Ok(sheet.clone());
+    }
+    let bytes = reqwest::get(url.as_str())
+        .await
+        .map_err(|e| Error::Image(url.clone(), e.to_string()))?
+        .bytes()
+        .await
+        .map_err(|e| Error::Image(url.clone(), e.to_string()))?;
+    //Decoding a sheet takes a while, which would block the other futures of the executor
+    let sheet = tokio::task::spawn_blocking(move || image::load_from_memory(&bytes))
+        .await
+        .map_err(|e| Error::Image(url.clone(), e.to_string()))?
+        .map(Arc::new)
+        .map_err(|e| Error::Image(url.clone(), e.to_string()))?;
+    SHEET_CACHE.lock().unwrap().cache_set(url, sheet.clone());
+    Ok(sheet)
+}
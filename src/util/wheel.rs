@@ -0,0 +1,111 @@
+use iced_native::event::{self, Event};
+use iced_native::layout::{self, Layout};
+use iced_native::mouse::{self, ScrollDelta};
+use iced_native::overlay;
+use iced_native::{Clipboard, Element, Hasher, Length, Point, Rectangle, Widget};
+use std::hash::Hash;
+
+/// Pixels of a smooth scroll counted as one line, the same as iced's scrollable uses
+const PIXELS_PER_LINE: f32 = 60.0;
+
+/// Turns wheel movements over the content into messages, with the lines scrolled up
+pub struct WheelArea<'a, Message, Renderer> {
+    content: Element<'a, Message, Renderer>,
+    on_scroll: Box<dyn Fn(f32) -> Message + 'a>,
+}
+
+impl<'a, Message, Renderer> WheelArea<'a, Message, Renderer> {
+    pub fn new<E, F>(content: E, on_scroll: F) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+        F: 'a + Fn(f32) -> Message,
+    {
+        WheelArea {
+            content: content.into(),
+            on_scroll: Box::new(on_scroll),
+        }
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for WheelArea<'a, Message, Renderer>
+where
+    Renderer: iced_native::Renderer,
+{
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        self.content
+            .draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        let status = self.content.on_event(
+            event.clone(),
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        );
+        if status == event::Status::Captured || !layout.bounds().contains(cursor_position) {
+            return status;
+        }
+        match event {
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let lines = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_LINE,
+                };
+                messages.push((self.on_scroll)(lines));
+                event::Status::Captured
+            }
+            _ => status,
+        }
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<overlay::Element<'_, Message, Renderer>> {
+        self.content.overlay(layout)
+    }
+}
+
+impl<'a, Message, Renderer> From<WheelArea<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + iced_native::Renderer,
+    Message: 'a,
+{
+    fn from(area: WheelArea<'a, Message, Renderer>) -> Self {
+        Element::new(area)
+    }
+}
@@ -1,5 +1,6 @@
 use reciprocity_communication::messages::oauth2::url::ParseError;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
@@ -9,6 +10,16 @@ pub enum SearchError {
     Serde(Arc<serde_json::Error>),
 }
 
+impl Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::UrlParse(e) => write!(f, "Invalid search backend: {}", e),
+            SearchError::Reqwest(e) => write!(f, "{}", e),
+            SearchError::Serde(e) => write!(f, "Unexpected response: {}", e),
+        }
+    }
+}
+
 impl From<ParseError> for SearchError {
     fn from(e: ParseError) -> Self {
         SearchError::UrlParse(Arc::new(e))
@@ -27,10 +38,10 @@ impl From<serde_json::Error> for SearchError {
     }
 }
 
-pub async fn search(q: String) -> Result<(String, Vec<Video>), SearchError> {
-    let base = "http://youtube-scrape.herokuapp.com/api/search?page=1".to_string();
-    let url = reqwest::Url::parse_with_params(&base, &[("q", q.clone())])?;
-    let res = reqwest::get(url).await.unwrap().text().await?;
+/// Searches with a youtube-scrape compatible backend, for example [`crate::config::DEFAULT_SEARCH_BACKEND`]
+pub async fn search(backend: String, q: String) -> Result<(String, Vec<Video>), SearchError> {
+    let url = reqwest::Url::parse_with_params(&backend, &[("q", q.clone())])?;
+    let res = reqwest::get(url).await?.text().await?;
     let mut res: SearchResult = serde_json::from_str(&res)?;
     let results: Vec<_> = res.results.drain(..).map(|r| r.video).flatten().collect();
 
@@ -47,7 +58,7 @@ pub struct VideoResult {
     video: Option<Video>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Video {
     pub id: String,
     pub title: String,